@@ -0,0 +1,127 @@
+use crate::field::InputField;
+
+/// Delayed auto shift at 60 ticks per second: 10 ticks, about 167ms.
+pub const DAS: u32 = 10;
+/// Auto repeat rate: 2 ticks, about 33ms.
+pub const ARR: u32 = 2;
+/// Soft drop rate: a row every 2 ticks, 30 rows a second.
+pub const SDR: u32 = 2;
+
+/// Turns held Left and Right keys into shifts. The caller makes the first
+/// shift of a press, and once the key was held for `das` ticks the figure
+/// shifts every `arr` ticks. With `arr` 0 it goes straight to the wall
+/// instead.
+///
+/// A held SoftDrop key likewise drops the figure a row every `sdr` ticks
+/// after the first row of the press, without waiting for the keyboard
+/// repeat.
+///
+/// Repeated shifts are handed to an `apply` callback that returns whether
+/// the figure moved, usually `Field::process_input`.
+pub struct AutoShift {
+    das: u32,
+    arr: u32,
+    sdr: u32,
+    left: bool,
+    right: bool,
+    /// Direction that repeats, the one pressed last.
+    direction: Option<InputField>,
+    /// Ticks the direction has been held.
+    held: u32,
+    /// Ticks SoftDrop has been held, `None` while it isn't.
+    soft_drop: Option<u32>,
+}
+
+impl AutoShift {
+    pub fn new(das: u32, arr: u32, sdr: u32) -> Self {
+        AutoShift {
+            das,
+            arr,
+            sdr: sdr.max(1),
+            left: false,
+            right: false,
+            direction: None,
+            held: 0,
+            soft_drop: None,
+        }
+    }
+
+    /// Starts holding `input`. Returns `false` for anything but Left,
+    /// Right and SoftDrop and for repeated presses of a key already held,
+    /// which are ignored.
+    pub fn press(&mut self, input: InputField) -> bool {
+        let held = match input {
+            InputField::Left => &mut self.left,
+            InputField::Right => &mut self.right,
+            InputField::SoftDrop => {
+                if self.soft_drop.is_some() {
+                    return false;
+                }
+                self.soft_drop = Some(0);
+                return true;
+            }
+            _ => return false,
+        };
+        if *held {
+            return false;
+        }
+        *held = true;
+        self.direction = Some(input);
+        self.held = 0;
+        true
+    }
+
+    /// Stops holding `input`. If the other direction is still held, it
+    /// starts charging again.
+    pub fn release(&mut self, input: InputField) {
+        let other = match input {
+            InputField::Left => {
+                self.left = false;
+                self.right.then_some(InputField::Right)
+            }
+            InputField::Right => {
+                self.right = false;
+                self.left.then_some(InputField::Left)
+            }
+            InputField::SoftDrop => {
+                self.soft_drop = None;
+                return;
+            }
+            _ => return,
+        };
+        if self.direction == Some(input) {
+            self.direction = other;
+            self.held = 0;
+        }
+    }
+
+    /// Advances the held keys by one tick, shifting and dropping if it is
+    /// due.
+    pub fn tick(&mut self, mut apply: impl FnMut(InputField) -> bool) {
+        if let Some(held) = &mut self.soft_drop {
+            *held += 1;
+            if held.is_multiple_of(self.sdr) {
+                apply(InputField::SoftDrop);
+            }
+        }
+        let direction = match self.direction {
+            Some(direction) => direction,
+            None => return,
+        };
+        self.held += 1;
+        if self.held < self.das {
+            return;
+        }
+        if self.arr == 0 {
+            while apply(direction) {}
+        } else if (self.held - self.das).is_multiple_of(self.arr) {
+            apply(direction);
+        }
+    }
+}
+
+impl Default for AutoShift {
+    fn default() -> Self {
+        AutoShift::new(DAS, ARR, SDR)
+    }
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use winit::event::VirtualKeyCode;
+
+/// Everything a key can be bound to.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Control {
+    Left,
+    Right,
+    Rotate,
+    RotateCcw,
+    Rotate180,
+    SoftDrop,
+    HardDrop,
+    Hold,
+    Pause,
+    Restart,
+    Quit,
+    Fullscreen,
+}
+
+impl Control {
+    pub const ALL: [Control; 12] = [
+        Control::Left,
+        Control::Right,
+        Control::Rotate,
+        Control::RotateCcw,
+        Control::Rotate180,
+        Control::SoftDrop,
+        Control::HardDrop,
+        Control::Hold,
+        Control::Pause,
+        Control::Restart,
+        Control::Quit,
+        Control::Fullscreen,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Control::Left => "left",
+            Control::Right => "right",
+            Control::Rotate => "rotate",
+            Control::RotateCcw => "rotate ccw",
+            Control::Rotate180 => "rotate 180",
+            Control::SoftDrop => "soft drop",
+            Control::HardDrop => "hard drop",
+            Control::Hold => "hold",
+            Control::Pause => "pause",
+            Control::Restart => "restart",
+            Control::Quit => "quit",
+            Control::Fullscreen => "fullscreen",
+        }
+    }
+}
+
+/// Keys of every control, any number of them each. Stored as TOML in the
+/// config directory, controls missing there keep their default keys.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    left: Vec<VirtualKeyCode>,
+    right: Vec<VirtualKeyCode>,
+    rotate: Vec<VirtualKeyCode>,
+    rotate_ccw: Vec<VirtualKeyCode>,
+    rotate_180: Vec<VirtualKeyCode>,
+    soft_drop: Vec<VirtualKeyCode>,
+    hard_drop: Vec<VirtualKeyCode>,
+    hold: Vec<VirtualKeyCode>,
+    pause: Vec<VirtualKeyCode>,
+    restart: Vec<VirtualKeyCode>,
+    quit: Vec<VirtualKeyCode>,
+    fullscreen: Vec<VirtualKeyCode>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use VirtualKeyCode::*;
+        Bindings {
+            left: vec![Left],
+            right: vec![Right],
+            rotate: vec![Up, X],
+            rotate_ccw: vec![Z],
+            rotate_180: vec![A],
+            soft_drop: vec![Down],
+            hard_drop: vec![Space],
+            hold: vec![C, LShift],
+            pause: vec![P],
+            restart: vec![Return],
+            quit: vec![Escape],
+            fullscreen: vec![F11],
+        }
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("tetris").join("bindings.toml"))
+}
+
+impl Bindings {
+    /// Reads the bindings from the config directory, the defaults if there
+    /// are none or they can't be read.
+    pub fn load() -> Self {
+        let path = match path() {
+            Some(path) => path,
+            None => return Bindings::default(),
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => return Bindings::default(),
+        };
+        toml::from_str(&text).unwrap_or_else(|e| {
+            eprintln!("can't read bindings {}: {}", path.display(), e);
+            Bindings::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let path = match path() {
+            Some(path) => path,
+            None => return,
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                let text = toml::to_string(self).expect("bindings are TOML");
+                fs::write(&path, text)
+            });
+        if let Err(e) = result {
+            eprintln!("can't write bindings {}: {}", path.display(), e);
+        }
+    }
+
+    fn keys(&self, control: Control) -> &Vec<VirtualKeyCode> {
+        match control {
+            Control::Left => &self.left,
+            Control::Right => &self.right,
+            Control::Rotate => &self.rotate,
+            Control::RotateCcw => &self.rotate_ccw,
+            Control::Rotate180 => &self.rotate_180,
+            Control::SoftDrop => &self.soft_drop,
+            Control::HardDrop => &self.hard_drop,
+            Control::Hold => &self.hold,
+            Control::Pause => &self.pause,
+            Control::Restart => &self.restart,
+            Control::Quit => &self.quit,
+            Control::Fullscreen => &self.fullscreen,
+        }
+    }
+
+    fn keys_mut(&mut self, control: Control) -> &mut Vec<VirtualKeyCode> {
+        match control {
+            Control::Left => &mut self.left,
+            Control::Right => &mut self.right,
+            Control::Rotate => &mut self.rotate,
+            Control::RotateCcw => &mut self.rotate_ccw,
+            Control::Rotate180 => &mut self.rotate_180,
+            Control::SoftDrop => &mut self.soft_drop,
+            Control::HardDrop => &mut self.hard_drop,
+            Control::Hold => &mut self.hold,
+            Control::Pause => &mut self.pause,
+            Control::Restart => &mut self.restart,
+            Control::Quit => &mut self.quit,
+            Control::Fullscreen => &mut self.fullscreen,
+        }
+    }
+
+    /// Control `key` is bound to. A key bound twice goes to the control
+    /// listed first in `Control::ALL`.
+    pub fn control(&self, key: VirtualKeyCode) -> Option<Control> {
+        Control::ALL
+            .iter()
+            .copied()
+            .find(|&control| self.keys(control).contains(&key))
+    }
+}
+
+/// Key that moves the rebind screen on to the next control. It can't be
+/// bound to anything.
+pub const REBIND_KEY: VirtualKeyCode = VirtualKeyCode::F1;
+
+/// The rebind screen: asks for the keys of every control in turn. Keys
+/// pressed for a control replace its old ones, a control left without a
+/// key press keeps them.
+pub struct Rebind {
+    bindings: Bindings,
+    control: usize,
+    keys: Vec<VirtualKeyCode>,
+}
+
+impl Rebind {
+    pub fn new(bindings: Bindings) -> Self {
+        Rebind {
+            bindings,
+            control: 0,
+            keys: Vec::new(),
+        }
+    }
+
+    /// The current control and its keys, the ones pressed so far or else
+    /// the old ones.
+    fn current(&self) -> (Control, String) {
+        let control = Control::ALL[self.control];
+        let keys = if self.keys.is_empty() {
+            self.bindings.keys(control)
+        } else {
+            &self.keys
+        };
+        let keys: Vec<_> =
+            keys.iter().map(|key| format!("{:?}", key)).collect();
+        (control, keys.join(" "))
+    }
+
+    /// What to show in the window title.
+    pub fn prompt(&self) -> String {
+        let (control, keys) = self.current();
+        format!(
+            "tetris - keys for {}: {} ({:?} for next)",
+            control.name(),
+            keys,
+            REBIND_KEY,
+        )
+    }
+
+    /// What to show on the screen, line by line.
+    pub fn lines(&self) -> Vec<String> {
+        let (control, keys) = self.current();
+        vec![
+            format!("keys for {}", control.name()),
+            keys,
+            format!("{:?} for next", REBIND_KEY),
+        ]
+    }
+
+    pub fn press(&mut self, key: VirtualKeyCode) {
+        if key != REBIND_KEY && !self.keys.contains(&key) {
+            self.keys.push(key);
+        }
+    }
+
+    /// Stores the keys pressed for the current control, taking them away
+    /// from any other control, and moves on. Returns the new bindings
+    /// after the last control.
+    pub fn next(&mut self) -> Option<Bindings> {
+        let keys = std::mem::take(&mut self.keys);
+        if !keys.is_empty() {
+            for control in Control::ALL {
+                self.bindings
+                    .keys_mut(control)
+                    .retain(|key| !keys.contains(key));
+            }
+            *self.bindings.keys_mut(Control::ALL[self.control]) = keys;
+        }
+        self.control += 1;
+        if self.control < Control::ALL.len() {
+            None
+        } else {
+            Some(self.bindings.clone())
+        }
+    }
+}
@@ -0,0 +1,1711 @@
+use crate::generator::{PieceGenerator, SevenBag};
+use crate::scoring::{Guideline, LineClear, ScoringRule, TSpin};
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng, SeedableRng,
+};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::Duration;
+
+/// Default size of the visible part of the field.
+pub const FIELD_WIDTH: usize = 10;
+pub const FIELD_HEIGHT: usize = 20;
+/// Default number of buffer rows above the visible part. Figures spawn in
+/// the lowest two of them.
+pub const HIDDEN_ROWS: usize = 20;
+
+/// Ticks of game logic per second.
+pub const TICKS_PER_SECOND: u32 = 60;
+/// Length of a tick, rounded up to whole nanoseconds so that a number of
+/// ticks never falls short of the time they stand for.
+pub const TICK: Duration =
+    Duration::from_nanos(1_000_000_000_u64.div_ceil(TICKS_PER_SECOND as u64));
+/// Gravity builds up in fractions of a row per tick, `ROW` of them make
+/// one row.
+const ROW: u32 = 1 << 16;
+
+/// Time a landed figure waits before it locks.
+const LOCK_DELAY: Duration = Duration::from_millis(500);
+/// Moves or rotations that may restart the lock delay on one row.
+const MAX_LOCK_RESETS: u32 = 15;
+
+/// Ticks a new figure waits after a lock by default.
+pub const SPAWN_DELAY: u32 = 6;
+
+/// Lines to clear for every level.
+const LINES_PER_LEVEL: u32 = 10;
+
+/// Upcoming figures shown by default.
+const NEXT_LEN: usize = 5;
+/// Rows taken by every figure of the next queue in the left column.
+const NEXT_SLOT_HEIGHT: usize = 3;
+
+/// First row of the hold box in the column right of the field.
+const HOLD_ROW: usize = 2;
+/// Row of the level number in the column right of the field.
+const LEVEL_ROW: usize = HOLD_ROW + 5;
+
+/// Events kept for `drain_events`, older ones are dropped beyond that.
+const MAX_EVENTS: usize = 256;
+
+/// Gravity of the guideline curve on levels 1 to 20 in `ROW`ths of a
+/// row per tick, worked out once from `(0.8 - (level - 1) * 0.007) ^
+/// (level - 1)` seconds per row and rounded. Higher levels stay at level
+/// 20, the formula turns negative further up.
+#[rustfmt::skip]
+const GUIDELINE_GRAVITY: [u32; 20] = [
+    1092, 1377, 1768, 2311, 3075, 4169, 5759, 8107, 11634, 17026,
+    25416, 38709, 60169, 95483, 154742, 256187, 433425, 749597, 1325716,
+    2398490,
+];
+
+/// How fast figures fall on each level.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum GravityCurve {
+    /// `(0.8 - (level - 1) * 0.007) ^ (level - 1)` seconds per row, up to
+    /// level 20.
+    Guideline,
+    /// The NES frame table, 48 frames per row on NES level 0 down to one
+    /// frame from NES level 29 on, a tick standing in for a frame. Levels
+    /// here start at 1, so level 1 plays like NES level 0.
+    Nes,
+}
+
+impl GravityCurve {
+    /// Fractions of a row, `ROW` to the row, figures fall every tick on
+    /// `level`. Only integers so every platform plays the same.
+    fn per_tick(&self, level: u32) -> u32 {
+        match self {
+            GravityCurve::Guideline => {
+                let level = level.clamp(1, GUIDELINE_GRAVITY.len() as u32);
+                GUIDELINE_GRAVITY[level as usize - 1]
+            }
+            GravityCurve::Nes => {
+                let level = level.saturating_sub(1);
+                let frames = match level {
+                    0..=8 => 48 - level * 5,
+                    9 => 6,
+                    10..=12 => 5,
+                    13..=15 => 4,
+                    16..=18 => 3,
+                    19..=28 => 2,
+                    _ => 1,
+                };
+                // rounded up so a row takes `frames` ticks, not one more
+                ROW.div_ceil(frames)
+            }
+        }
+    }
+
+    /// Time a figure takes to fall one row on `level`.
+    pub fn gravity(&self, level: u32) -> Duration {
+        let nanos =
+            TICK.as_nanos() * ROW as u128 / self.per_tick(level) as u128;
+        Duration::from_nanos(nanos as u64)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct Pos {
+    x: isize,
+    y: isize,
+}
+
+impl Pos {
+    fn advance(&self) -> Self {
+        Pos {
+            x: self.x,
+            y: self.y + 1,
+        }
+    }
+}
+
+/// Turn of a figure from its spawn orientation.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Rotation {
+    None,
+    Left,
+    Right,
+    Upside,
+}
+
+impl Rotation {
+    fn rotate(&self) -> Self {
+        match self {
+            Rotation::None => Rotation::Right,
+            Rotation::Left => Rotation::None,
+            Rotation::Right => Rotation::Upside,
+            Rotation::Upside => Rotation::Left,
+        }
+    }
+
+    fn rotate_ccw(&self) -> Self {
+        match self {
+            Rotation::None => Rotation::Left,
+            Rotation::Left => Rotation::Upside,
+            Rotation::Right => Rotation::None,
+            Rotation::Upside => Rotation::Right,
+        }
+    }
+
+    fn rotate_180(&self) -> Self {
+        self.rotate().rotate()
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Rotation::None => 0,
+            Rotation::Right => 1,
+            Rotation::Upside => 2,
+            Rotation::Left => 3,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Color {
+    Transparent,
+    Red,
+    Green,
+    Blue,
+    Cyan,
+    Magenta,
+    Yellow,
+    Orange,
+    Gray,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum FigureKind {
+    Bar,
+    PZ,
+    NZ,
+    PL,
+    NL,
+    Square,
+    T,
+}
+
+impl Distribution<FigureKind> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> FigureKind {
+        match rng.gen_range(0..7) {
+            0 => FigureKind::Bar,
+            1 => FigureKind::PZ,
+            2 => FigureKind::NZ,
+            3 => FigureKind::PL,
+            4 => FigureKind::NL,
+            5 => FigureKind::Square,
+            6 => FigureKind::T,
+            _ => panic!(),
+        }
+    }
+}
+
+impl From<FigureKind> for (Pos, HashMap<Rotation, [[u8; 4]; 4]>) {
+    fn from(fig: FigureKind) -> (Pos, HashMap<Rotation, [[u8; 4]; 4]>) {
+        #[rustfmt::skip]
+    let t = match fig {
+      FigureKind::Bar => (
+        Pos { x: 3, y: 0 },
+        [
+          (
+            Rotation::None,
+            [[0, 0, 0, 0],
+             [1, 1, 1, 1],
+             [0, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Right,
+            [[0, 0, 1, 0],
+             [0, 0, 1, 0],
+             [0, 0, 1, 0],
+             [0, 0, 1, 0]],
+          ),
+          (
+            Rotation::Upside,
+            [[0, 0, 0, 0],
+             [0, 0, 0, 0],
+             [1, 1, 1, 1],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Left,
+            [[0, 1, 0, 0],
+             [0, 1, 0, 0],
+             [0, 1, 0, 0],
+             [0, 1, 0, 0]],
+          ),
+        ],
+      ),
+      FigureKind::PZ => (
+        Pos { x: 3, y: 0 },
+        [
+          (
+            Rotation::None,
+            [[1, 1, 0, 0],
+             [0, 1, 1, 0],
+             [0, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Right,
+            [[0, 0, 1, 0],
+             [0, 1, 1, 0],
+             [0, 1, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Upside,
+            [[0, 0, 0, 0],
+             [1, 1, 0, 0],
+             [0, 1, 1, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Left,
+            [[0, 1, 0, 0],
+             [1, 1, 0, 0],
+             [1, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+        ],
+      ),
+      FigureKind::NZ => (
+        Pos { x: 3, y: 0 },
+        [
+          (
+            Rotation::None,
+            [[0, 1, 1, 0],
+             [1, 1, 0, 0],
+             [0, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Right,
+            [[0, 1, 0, 0],
+             [0, 1, 1, 0],
+             [0, 0, 1, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Upside,
+            [[0, 0, 0, 0],
+             [0, 1, 1, 0],
+             [1, 1, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Left,
+            [[1, 0, 0, 0],
+             [1, 1, 0, 0],
+             [0, 1, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+        ],
+      ),
+      FigureKind::PL => (
+        Pos { x: 3, y: 0 },
+        [
+          (
+            Rotation::None,
+            [[0, 0, 1, 0],
+             [1, 1, 1, 0],
+             [0, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Right,
+            [[0, 1, 0, 0],
+             [0, 1, 0, 0],
+             [0, 1, 1, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Upside,
+            [[0, 0, 0, 0],
+             [1, 1, 1, 0],
+             [1, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Left,
+            [[1, 1, 0, 0],
+             [0, 1, 0, 0],
+             [0, 1, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+        ],
+      ),
+      FigureKind::NL => (
+        Pos { x: 3, y: 0 },
+        [
+          (
+            Rotation::None,
+            [[1, 0, 0, 0],
+             [1, 1, 1, 0],
+             [0, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Right,
+            [[0, 1, 1, 0],
+             [0, 1, 0, 0],
+             [0, 1, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Upside,
+            [[0, 0, 0, 0],
+             [1, 1, 1, 0],
+             [0, 0, 1, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Left,
+            [[0, 1, 0, 0],
+             [0, 1, 0, 0],
+             [1, 1, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+        ],
+      ),
+      FigureKind::Square => (
+        Pos { x: 3, y: 0 },
+        [
+          (
+            Rotation::None,
+            [[0, 1, 1, 0],
+             [0, 1, 1, 0],
+             [0, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Right,
+            [[0, 1, 1, 0],
+             [0, 1, 1, 0],
+             [0, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Upside,
+            [[0, 1, 1, 0],
+             [0, 1, 1, 0],
+             [0, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Left,
+            [[0, 1, 1, 0],
+             [0, 1, 1, 0],
+             [0, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+        ],
+      ),
+      FigureKind::T => (
+        Pos { x: 3, y: 0 },
+        [
+          (
+            Rotation::None,
+            [[0, 1, 0, 0],
+             [1, 1, 1, 0],
+             [0, 0, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Right,
+            [[0, 1, 0, 0],
+             [0, 1, 1, 0],
+             [0, 1, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Upside,
+            [[0, 0, 0, 0],
+             [1, 1, 1, 0],
+             [0, 1, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+          (
+            Rotation::Left,
+            [[0, 1, 0, 0],
+             [1, 1, 0, 0],
+             [0, 1, 0, 0],
+             [0, 0, 0, 0]],
+          ),
+        ],
+      ),
+    };
+        (t.0, HashMap::<_, _>::from_iter(t.1))
+    }
+}
+
+impl FigureKind {
+    pub const ALL: [FigureKind; 7] = [
+        FigureKind::Bar,
+        FigureKind::PZ,
+        FigureKind::NZ,
+        FigureKind::PL,
+        FigureKind::NL,
+        FigureKind::Square,
+        FigureKind::T,
+    ];
+
+    fn get_rect(self, rotation: Rotation) -> [[u8; 4]; 4] {
+        let (_, t) = self.into();
+        *t.get(&rotation).unwrap()
+    }
+
+    fn get_pos(self) -> Pos {
+        let (t, _) = self.into();
+        t
+    }
+
+    /// Guideline color of the figure.
+    pub fn color(self) -> Color {
+        match self {
+            FigureKind::Bar => Color::Cyan,
+            FigureKind::PZ => Color::Red,
+            FigureKind::NZ => Color::Green,
+            FigureKind::PL => Color::Orange,
+            FigureKind::NL => Color::Blue,
+            FigureKind::Square => Color::Yellow,
+            FigureKind::T => Color::Magenta,
+        }
+    }
+
+    /// Kicks for rotating from `from` to `to`. Counter-clockwise kicks are
+    /// the clockwise ones of the opposite rotation, negated.
+    fn kicks(self, from: Rotation, to: Rotation) -> Vec<(isize, isize)> {
+        let cw = match self {
+            FigureKind::Square => return vec![(0, 0)],
+            FigureKind::Bar => &I_KICKS,
+            _ => &JLSTZ_KICKS,
+        };
+        if to == from.rotate() {
+            cw[from.index()].to_vec()
+        } else if to == from.rotate_ccw() {
+            cw[to.index()].iter().map(|(x, y)| (-x, -y)).collect()
+        } else {
+            HALF_TURN_KICKS[from.index()].to_vec()
+        }
+    }
+}
+
+/// SRS wall kicks for J, L, S, T and Z, tried in order for a clockwise
+/// rotation out of each state. Offsets are `(x, y)` with `y` pointing up.
+const JLSTZ_KICKS: [[(isize, isize); 5]; 4] = [
+    // 0 -> R
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    // R -> 2
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    // 2 -> L
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+    // L -> 0
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+];
+
+/// Kicks for a 180 degree rotation out of each state, shared by all the
+/// figures that have kicks. SRS itself has none, these follow the common
+/// SRS+ extension.
+const HALF_TURN_KICKS: [[(isize, isize); 6]; 4] = [
+    // 0 -> 2
+    [(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)],
+    // R -> L
+    [(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],
+    // 2 -> 0
+    [(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)],
+    // L -> R
+    [(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],
+];
+
+/// SRS wall kicks for the I piece, laid out like `JLSTZ_KICKS`.
+const I_KICKS: [[(isize, isize); 5]; 4] = [
+    // 0 -> R
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+    // R -> 2
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+    // 2 -> L
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    // L -> 0
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+];
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+struct Figure {
+    kind: FigureKind,
+    color: Color,
+}
+
+impl Figure {
+    fn deal(generator: &mut dyn PieceGenerator, rng: &mut ChaCha8Rng) -> Self {
+        let kind = generator.next_kind(rng);
+        Figure {
+            kind,
+            color: kind.color(),
+        }
+    }
+
+    fn get_rect(&self, rotation: Rotation) -> [[u8; 4]; 4] {
+        self.kind.get_rect(rotation)
+    }
+
+    /// Glyph at (`x`, `y`) of a 4x4 preview box with the figure pushed to
+    /// its top-left corner.
+    fn preview(&self, x: usize, y: usize) -> Glyph {
+        let rect = self.get_rect(Rotation::None);
+
+        let dy = rect
+            .iter()
+            .enumerate()
+            .find(|(_, x)| x.iter().any(|x| x != &0))
+            .map_or(0, |x| x.0);
+
+        let dx = transpose(&rect)
+            .iter()
+            .enumerate()
+            .find(|(_, x)| x.iter().any(|x| x != &0))
+            .map_or(0, |x| x.0);
+
+        let x = x + dx;
+        let y = y + dy;
+        if x < 4 && y < 4 && rect[y][x] == 1 {
+            Glyph::Color(self.color)
+        } else {
+            Glyph::Color(Color::Transparent)
+        }
+    }
+}
+
+fn transpose<T: Default + Copy, const N: usize, const M: usize>(
+    r: &[[T; N]; M],
+) -> [[T; M]; N] {
+    let mut res = [[Default::default(); M]; N];
+    for i in 0..N {
+        for j in 0..M {
+            res[i][j] = r[j][i];
+        }
+    }
+    res
+}
+
+/// The playing field and everything in it.
+///
+/// Whatever happens is also queued as a `GameEvent`. A caller that never
+/// calls `drain_events` loses all but the last `MAX_EVENTS` (256) of them.
+#[derive(Serialize, Deserialize)]
+pub struct Field {
+    width: usize,
+    height: usize,
+    hidden: usize,
+    pieces: Vec<Vec<Color>>,
+    current_figure: Figure,
+    current_figure_pos: Pos,
+    current_figure_rotation: Rotation,
+    next_figures: VecDeque<Figure>,
+    next_len: usize,
+    generator: Box<dyn PieceGenerator>,
+    seed: u64,
+    rng: ChaCha8Rng,
+    lock_delay: Duration,
+    lock_timer: Duration,
+    /// Gravity built up since the figure last fell, in `ROW`ths of a row.
+    fall: u32,
+    lock_resets: u32,
+    max_lock_resets: u32,
+    lowest_row: isize,
+    last_move: LastMove,
+    hold_figure: Option<Figure>,
+    hold_used: bool,
+    state: FieldState,
+    score: u32,
+    scoring: Box<dyn ScoringRule>,
+    combo: Option<u32>,
+    back_to_back: bool,
+    lines: u32,
+    level: u32,
+    gravity_curve: GravityCurve,
+    /// Events are for the caller that was running the game, a restored
+    /// field starts without any.
+    #[serde(skip)]
+    events: VecDeque<GameEvent>,
+}
+
+impl Field {
+    pub fn new() -> Self {
+        Field::with_seed(rand::thread_rng().gen())
+    }
+
+    /// Fields built with the same seed deal the same figures, so they end
+    /// up identical when fed the same inputs.
+    pub fn with_seed(seed: u64) -> Self {
+        Field::with_generator(Box::new(SevenBag::new()), seed)
+    }
+
+    pub fn with_generator(
+        mut generator: Box<dyn PieceGenerator>,
+        seed: u64,
+    ) -> Self {
+        let width = FIELD_WIDTH;
+        let height = FIELD_HEIGHT;
+        let hidden = HIDDEN_ROWS;
+        let v = vec![vec![Color::Transparent; width]; height + hidden];
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let current_figure = Figure::deal(&mut *generator, &mut rng);
+        let next_figures = (0..NEXT_LEN)
+            .map(|_| Figure::deal(&mut *generator, &mut rng))
+            .collect();
+        let mut field = Field {
+            width,
+            height,
+            hidden,
+            pieces: v,
+            current_figure,
+            current_figure_pos: current_figure.kind.get_pos(),
+            current_figure_rotation: Rotation::None,
+            next_figures,
+            next_len: NEXT_LEN,
+            generator,
+            seed,
+            rng,
+            lock_delay: LOCK_DELAY,
+            lock_timer: LOCK_DELAY,
+            fall: 0,
+            lock_resets: 0,
+            max_lock_resets: MAX_LOCK_RESETS,
+            lowest_row: 0,
+            last_move: LastMove::Shift,
+            hold_figure: None,
+            hold_used: false,
+            state: FieldState::Playing,
+            score: 0,
+            scoring: Box::new(Guideline),
+            combo: None,
+            back_to_back: false,
+            lines: 0,
+            level: 1,
+            gravity_curve: GravityCurve::Guideline,
+            events: VecDeque::new(),
+        };
+        field.spawn_figure(current_figure);
+        field
+    }
+
+    /// Seed the field was built with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Visible width of the field.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Visible height of the field.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Buffer rows above the visible part of the field.
+    pub fn hidden(&self) -> usize {
+        self.hidden
+    }
+
+    /// Resizes the field to `width` x `height` visible cells with `hidden`
+    /// buffer rows above them. Clears the board and moves the current
+    /// figure back to its spawn position. A size figures can't be played
+    /// on leaves the field as it was.
+    pub fn set_size(
+        &mut self,
+        width: usize,
+        height: usize,
+        hidden: usize,
+    ) -> Result<(), SizeError> {
+        if width < 4 {
+            return Err(SizeError::TooNarrow);
+        }
+        if height < 1 {
+            return Err(SizeError::NoRows);
+        }
+        if hidden < 2 {
+            return Err(SizeError::TooFewHidden);
+        }
+        self.width = width;
+        self.height = height;
+        self.hidden = hidden;
+        self.pieces = vec![vec![Color::Transparent; width]; height + hidden];
+        self.place_at_spawn(self.current_figure);
+        Ok(())
+    }
+
+    /// Rows of the board, hidden ones included.
+    pub fn rows(&self) -> usize {
+        self.height + self.hidden
+    }
+
+    /// Block at column `x` and row `y` of the board, `Transparent` if the
+    /// cell is empty. Rows count from the top of the hidden ones, the
+    /// visible field starts at row `hidden()`.
+    pub fn cell(&self, x: usize, y: usize) -> Color {
+        self.pieces[y][x]
+    }
+
+    /// The falling figure.
+    pub fn current(&self) -> Placement {
+        Placement {
+            kind: self.current_figure.kind,
+            rotation: self.current_figure_rotation,
+            x: self.current_figure_pos.x,
+            y: self.current_figure_pos.y,
+        }
+    }
+
+    /// Upcoming figures as shown, the one dealt next first.
+    pub fn next_queue(&self) -> impl Iterator<Item = FigureKind> + '_ {
+        self.next_figures
+            .iter()
+            .take(self.next_len)
+            .map(|figure| figure.kind)
+    }
+
+    /// Figure in the hold box.
+    pub fn hold(&self) -> Option<FigureKind> {
+        self.hold_figure.map(|figure| figure.kind)
+    }
+
+    /// Hold was used already since the last figure locked.
+    pub fn hold_used(&self) -> bool {
+        self.hold_used
+    }
+
+    /// Spawn position of `kind`, centered like on a default sized field
+    /// and in the two lowest buffer rows.
+    fn spawn_pos(&self, kind: FigureKind) -> Pos {
+        let pos = kind.get_pos();
+        Pos {
+            x: pos.x + (self.width as isize - FIELD_WIDTH as isize) / 2,
+            y: pos.y + self.hidden as isize - 2,
+        }
+    }
+
+    pub fn state(&self) -> FieldState {
+        self.state
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Lines cleared so far.
+    pub fn lines(&self) -> u32 {
+        self.lines
+    }
+
+    /// Current level, starting at 1 and going up every 10 lines.
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    fn push_event(&mut self, event: GameEvent) {
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Takes the events queued since the last call, oldest first.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = GameEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    /// Switches between playing and paused. Does nothing once the game is
+    /// over.
+    pub fn toggle_pause(&mut self) {
+        self.state = match self.state {
+            FieldState::Playing => FieldState::Paused,
+            FieldState::Paused => FieldState::Playing,
+            FieldState::GameOver => FieldState::GameOver,
+        };
+    }
+
+    /// Writes the current figure into `pieces`. Returns `false` if all of
+    /// its cells ended up in the buffer rows.
+    fn place_current_figure(&mut self) -> bool {
+        let mut visible = false;
+        for (y, line) in self
+            .current_figure
+            .kind
+            .get_rect(self.current_figure_rotation)
+            .iter()
+            .enumerate()
+        {
+            for (x, b) in line.iter().enumerate() {
+                if b == &1 {
+                    let (fx, fy) = (
+                        (x as isize + self.current_figure_pos.x) as usize,
+                        (y as isize + self.current_figure_pos.y) as usize,
+                    );
+                    if (0..self.width).contains(&fx)
+                        && (0..self.rows()).contains(&fy)
+                    {
+                        self.pieces[fy][fx] = self.current_figure.color;
+                        visible |= fy >= self.hidden;
+                    }
+                }
+            }
+        }
+        visible
+    }
+
+    /// Applies `input` to the current figure. Returns `false` if it had no
+    /// effect, like a shift into a wall.
+    pub fn process_input(&mut self, input: InputField) -> bool {
+        if self.state != FieldState::Playing {
+            return false;
+        }
+        match input {
+            InputField::Left | InputField::Right => {
+                let old_pos = self.current_figure_pos;
+                let dx = if input == InputField::Left { -1 } else { 1 };
+                let moved = self.try_move(Pos {
+                    x: old_pos.x + dx,
+                    y: old_pos.y,
+                });
+                if moved {
+                    self.move_reset();
+                    self.push_event(GameEvent::Moved);
+                }
+                moved
+            }
+            InputField::Rotate
+            | InputField::RotateCcw
+            | InputField::Rotate180 => {
+                let rotation = match input {
+                    InputField::Rotate => {
+                        self.current_figure_rotation.rotate()
+                    }
+                    InputField::RotateCcw => {
+                        self.current_figure_rotation.rotate_ccw()
+                    }
+                    _ => self.current_figure_rotation.rotate_180(),
+                };
+                let rotated = self.try_rotate(rotation);
+                if rotated {
+                    self.move_reset();
+                    self.push_event(GameEvent::Rotated);
+                }
+                rotated
+            }
+            InputField::SoftDrop => {
+                let moved = self.try_move(self.current_figure_pos.advance());
+                if moved {
+                    self.score =
+                        self.score.saturating_add(self.scoring.soft_drop(1));
+                    self.push_event(GameEvent::Moved);
+                }
+                moved
+            }
+            InputField::Hold => {
+                if self.hold_used {
+                    return false;
+                }
+                let held = match self.hold_figure {
+                    Some(figure) => figure,
+                    None => self.take_next_figure(),
+                };
+                self.hold_figure = Some(self.current_figure);
+                self.push_event(GameEvent::Hold);
+                self.spawn_figure(held);
+                self.hold_used = true;
+                true
+            }
+        }
+    }
+
+    /// Runs the ticks of one gravity period, stopping early once the
+    /// figure fell a row. Returns what the figure did if it locked.
+    pub fn make_step(&mut self) -> Option<LineClear> {
+        let ticks = ROW.div_ceil(self.gravity_curve.per_tick(self.level));
+        let pos = self.current_figure_pos;
+        for _ in 0..ticks {
+            let clear = self.tick();
+            if clear.is_some()
+                || self.state != FieldState::Playing
+                || self.current_figure_pos != pos
+            {
+                return clear;
+            }
+        }
+        None
+    }
+
+    /// Advances the field by one `TICK`: gravity pulls the figure down,
+    /// several rows at once on high levels, and a landed figure uses up
+    /// its lock delay. Returns what the figure did if it locked.
+    pub fn tick(&mut self) -> Option<LineClear> {
+        if self.state != FieldState::Playing {
+            return None;
+        }
+        // no more than the whole board at once
+        let per_tick = self.gravity_curve.per_tick(self.level);
+        let most = ROW.saturating_mul(self.rows() as u32);
+        self.fall = self.fall.saturating_add(per_tick.min(most));
+        while self.fall >= ROW {
+            self.fall -= ROW;
+            if !self.try_move(self.current_figure_pos.advance()) {
+                break;
+            }
+        }
+        if self.does_collide(
+            self.current_figure_rotation,
+            self.current_figure_pos.advance(),
+        ) == CollideVariant::None
+        {
+            return None;
+        }
+        self.fall = 0;
+        if self.lock_timer <= TICK {
+            return self.next_figure();
+        }
+        self.lock_timer -= TICK;
+        None
+    }
+
+    /// Time until `make_step` should be called again, when the field is
+    /// not driven by `tick`.
+    pub fn gravity(&self) -> Duration {
+        self.gravity_curve.gravity(self.level)
+    }
+
+    pub fn set_scoring_rule(&mut self, rule: Box<dyn ScoringRule>) {
+        self.scoring = rule;
+    }
+
+    pub fn set_gravity_curve(&mut self, curve: GravityCurve) {
+        self.gravity_curve = curve;
+    }
+
+    /// Sets how long a landed figure waits before it locks, and how many
+    /// moves or rotations on the ground may restart that wait before the
+    /// figure falls to a lower row.
+    pub fn set_lock_delay(&mut self, delay: Duration, max_resets: u32) {
+        self.lock_delay = delay;
+        self.max_lock_resets = max_resets;
+        self.lock_timer = self.lock_timer.min(delay);
+    }
+
+    fn move_reset(&mut self) {
+        let grounded = self.does_collide(
+            self.current_figure_rotation,
+            self.current_figure_pos.advance(),
+        ) != CollideVariant::None;
+        if grounded && self.lock_resets < self.max_lock_resets {
+            self.lock_timer = self.lock_delay;
+            self.lock_resets += 1;
+        }
+    }
+
+    /// Falling below the lowest row reached so far gives the figure a fresh
+    /// lock delay and a fresh set of move resets.
+    fn update_lowest_row(&mut self) {
+        if self.current_figure_pos.y > self.lowest_row {
+            self.lowest_row = self.current_figure_pos.y;
+            self.lock_timer = self.lock_delay;
+            self.lock_resets = 0;
+        }
+    }
+
+    fn get_current_figure_shadow(&self) -> Pos {
+        let mut new_pos = self.current_figure_pos;
+        loop {
+            let new_pos_ = new_pos.advance();
+            if self.does_collide(self.current_figure_rotation, new_pos_)
+                == CollideVariant::BottomOrPieces
+            {
+                break;
+            }
+            new_pos = new_pos_;
+        }
+        new_pos
+    }
+
+    /// Hard drops and locks the figure. Returns what the figure did.
+    pub fn drop_figure(&mut self) -> Option<LineClear> {
+        if self.state != FieldState::Playing {
+            return None;
+        }
+        let shadow = self.get_current_figure_shadow();
+        let rows = (shadow.y - self.current_figure_pos.y) as u32;
+        self.score = self.score.saturating_add(self.scoring.hard_drop(rows));
+        if rows > 0 {
+            self.last_move = LastMove::Shift;
+        }
+        self.current_figure_pos = shadow;
+        self.next_figure()
+    }
+
+    /// Moves the current figure to `pos` if it fits there.
+    fn try_move(&mut self, pos: Pos) -> bool {
+        let fits = self.does_collide(self.current_figure_rotation, pos)
+            == CollideVariant::None;
+        if fits {
+            self.current_figure_pos = pos;
+            self.last_move = LastMove::Shift;
+            self.update_lowest_row();
+        }
+        fits
+    }
+
+    fn try_rotate(&mut self, new: Rotation) -> bool {
+        match self.try_rotation_replace(
+            self.current_figure_rotation,
+            new,
+            self.current_figure_pos,
+        ) {
+            Some((new_pos, kick)) => {
+                let quarter_turn =
+                    new != self.current_figure_rotation.rotate_180();
+                self.current_figure_rotation = new;
+                self.current_figure_pos = new_pos;
+                self.last_move = LastMove::Rotation {
+                    far_kick: quarter_turn && kick == 4,
+                };
+                self.update_lowest_row();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Tries the SRS kicks for rotating from `from` to `to` in order and
+    /// returns the first position where the rotated figure fits, along
+    /// with the index of the kick that got it there.
+    fn try_rotation_replace(
+        &self,
+        from: Rotation,
+        to: Rotation,
+        pos: Pos,
+    ) -> Option<(Pos, usize)> {
+        self.current_figure
+            .kind
+            .kicks(from, to)
+            .iter()
+            .map(|(dx, dy)| Pos {
+                x: pos.x + dx,
+                // kick tables point up, rows grow down
+                y: pos.y - dy,
+            })
+            .enumerate()
+            .find(|(_, pos)| {
+                self.does_collide(to, *pos) == CollideVariant::None
+            })
+            .map(|(kick, pos)| (pos, kick))
+    }
+
+    /// Applies the 3-corner rule to the current figure. A T whose last move
+    /// was a rotation and that has at least three of the corners of its
+    /// box blocked spun; it is a mini T-spin unless both corners next to
+    /// the pointing side are blocked or the rotation took the far kick.
+    fn detect_tspin(&self) -> TSpin {
+        let far_kick = match self.last_move {
+            LastMove::Rotation { far_kick }
+                if self.current_figure.kind == FigureKind::T =>
+            {
+                far_kick
+            }
+            _ => return TSpin::None,
+        };
+        // walls and the floor count as blocked
+        let blocked = |&(dx, dy): &(isize, isize)| {
+            let x = self.current_figure_pos.x + dx;
+            let y = self.current_figure_pos.y + dy;
+            x < 0
+                || x >= self.width as isize
+                || y >= self.rows() as isize
+                || (y >= 0
+                    && self.pieces[y as usize][x as usize]
+                        != Color::Transparent)
+        };
+        let front = match self.current_figure_rotation {
+            Rotation::None => [(0, 0), (2, 0)],
+            Rotation::Right => [(2, 0), (2, 2)],
+            Rotation::Upside => [(0, 2), (2, 2)],
+            Rotation::Left => [(0, 0), (0, 2)],
+        };
+        let corners = [(0, 0), (2, 0), (0, 2), (2, 2)]
+            .iter()
+            .filter(|corner| blocked(corner))
+            .count();
+        if corners < 3 {
+            TSpin::None
+        } else if far_kick || front.iter().all(blocked) {
+            TSpin::Full
+        } else {
+            TSpin::Mini
+        }
+    }
+
+    fn does_collide(&self, rot: Rotation, pos: Pos) -> CollideVariant {
+        for line in self.current_figure.get_rect(rot) {
+            for (x, b) in line.iter().enumerate() {
+                let real_x = x as isize + pos.x;
+                if b == &1 {
+                    if real_x < 0 {
+                        return CollideVariant::Left;
+                    }
+                    if real_x >= self.width as isize {
+                        return CollideVariant::Right;
+                    }
+                }
+            }
+        }
+        for (y, line) in self.current_figure.get_rect(rot).iter().enumerate() {
+            for (x, b) in line.iter().enumerate() {
+                let real_x = x as isize + pos.x;
+                let real_y = y as isize + pos.y;
+                if b == &1 {
+                    // nothing may leave the buffer rows at the top either
+                    if real_y < 0 || real_y >= self.rows() as isize {
+                        return CollideVariant::BottomOrPieces;
+                    }
+                    if (0..self.width).contains(&(real_x as usize))
+                        && (0..self.rows()).contains(&(real_y as usize))
+                        && self.pieces[real_y as usize][real_x as usize]
+                            != Color::Transparent
+                    {
+                        return CollideVariant::BottomOrPieces;
+                    }
+                }
+            }
+        }
+        CollideVariant::None
+    }
+
+    /// Locks the current figure and spawns the next one. Returns what the
+    /// locked figure did, or `None` if locking it ended the game.
+    fn next_figure(&mut self) -> Option<LineClear> {
+        let tspin = self.detect_tspin();
+        self.push_event(GameEvent::Locked {
+            kind: self.current_figure.kind,
+            tspin,
+        });
+        // lock out: the figure locked entirely above the visible field
+        if !self.place_current_figure() {
+            self.state = FieldState::GameOver;
+            self.push_event(GameEvent::GameOver);
+            return None;
+        }
+        let (n, min_row) = self.check_lines();
+        if n > 0 {
+            self.combo = Some(self.combo.map_or(0, |combo| combo + 1));
+        } else {
+            self.combo = None;
+        }
+        let clear = LineClear {
+            lines: n,
+            tspin,
+            min_row: min_row.saturating_sub(self.hidden),
+            field_height: self.height,
+            level: self.level,
+            combo: self.combo.unwrap_or(0),
+            back_to_back: self.back_to_back,
+            perfect_clear: n > 0
+                && self
+                    .pieces
+                    .iter()
+                    .flatten()
+                    .all(|c| c == &Color::Transparent),
+        };
+        self.score = self.score.saturating_add(self.scoring.lock(&clear));
+        if n > 0 {
+            self.push_event(GameEvent::LinesCleared(clear));
+            self.back_to_back = clear.is_difficult();
+            self.lines += n;
+            let level = 1 + self.lines / LINES_PER_LEVEL;
+            if level > self.level {
+                self.level = level;
+                self.push_event(GameEvent::LevelUp(level));
+            }
+        }
+        let figure = self.take_next_figure();
+        self.spawn_figure(figure);
+        self.hold_used = false;
+        Some(clear)
+    }
+
+    fn take_next_figure(&mut self) -> Figure {
+        let figure = self.next_figures.pop_front().unwrap();
+        self.fill_next_figures();
+        figure
+    }
+
+    fn fill_next_figures(&mut self) {
+        while self.next_figures.len() < self.next_len {
+            let figure = Figure::deal(&mut *self.generator, &mut self.rng);
+            self.next_figures.push_back(figure);
+        }
+    }
+
+    /// Sets how many upcoming figures are shown, at least one. Figures
+    /// already dealt are kept when the queue shrinks, so the sequence
+    /// stays the same.
+    pub fn set_next_len(&mut self, len: usize) {
+        self.next_len = len.max(1);
+        self.fill_next_figures();
+    }
+
+    fn spawn_figure(&mut self, figure: Figure) {
+        self.push_event(GameEvent::Spawned(figure.kind));
+        self.place_at_spawn(figure);
+    }
+
+    /// Puts `figure` at its spawn position with a fresh lock delay.
+    fn place_at_spawn(&mut self, figure: Figure) {
+        self.current_figure_rotation = Rotation::None;
+        self.current_figure = figure;
+        self.current_figure_pos = self.spawn_pos(figure.kind);
+        self.lowest_row = self.current_figure_pos.y;
+        self.lock_timer = self.lock_delay;
+        self.lock_resets = 0;
+        self.fall = 0;
+        self.last_move = LastMove::Shift;
+        // block out: the new figure overlaps the stack right at spawn
+        if self.does_collide(
+            self.current_figure_rotation,
+            self.current_figure_pos,
+        ) != CollideVariant::None
+        {
+            self.state = FieldState::GameOver;
+            self.push_event(GameEvent::GameOver);
+            return;
+        }
+        // figures drop into view right away when there is room
+        self.try_move(self.current_figure_pos.advance());
+    }
+
+    fn check_lines(&mut self) -> (u32, usize) {
+        let mut count = 0;
+        let mut min_row: usize = self.rows();
+        while let Some((y, _)) =
+            self.pieces.iter().enumerate().find(|(_, line)| {
+                line.iter().all(|x| x != &Color::Transparent)
+            })
+        {
+            count += 1;
+            min_row = min_row.min(y);
+            self.pieces.remove(y);
+            self.pieces.insert(0, vec![Color::Transparent; self.width]);
+        }
+
+        (count, min_row)
+    }
+}
+
+/// Last successful movement of the current figure.
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+enum LastMove {
+    Shift,
+    /// `far_kick` is set when a quarter turn needed the last SRS kick.
+    Rotation {
+        far_kick: bool,
+    },
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum CollideVariant {
+    None,
+    Left,
+    Right,
+    BottomOrPieces,
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        Field::new()
+    }
+}
+
+/// Something that happened on the field, queued for `drain_events`.
+#[derive(Copy, Clone, Debug)]
+pub enum GameEvent {
+    /// A figure entered the field, either dealt or swapped in from hold.
+    Spawned(FigureKind),
+    /// The player shifted or soft dropped the figure.
+    Moved,
+    Rotated,
+    Locked {
+        kind: FigureKind,
+        tspin: TSpin,
+    },
+    /// Always follows the `Locked` of the figure that cleared the lines.
+    LinesCleared(LineClear),
+    LevelUp(u32),
+    Hold,
+    GameOver,
+}
+
+/// Where a figure is on the board: the top-left corner of its 4x4 box in
+/// the coordinates of `Field::cell`, which may lie outside the board.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Placement {
+    pub kind: FigureKind,
+    pub rotation: Rotation,
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Placement {
+    /// Board cells the figure covers.
+    pub fn cells(&self) -> impl Iterator<Item = (isize, isize)> {
+        let rect = self.kind.get_rect(self.rotation);
+        let (x, y) = (self.x, self.y);
+        (0..4).flat_map(move |dy| {
+            (0..4)
+                .filter(move |&dx| rect[dy][dx] == 1)
+                .map(move |dx| (x + dx as isize, y + dy as isize))
+        })
+    }
+}
+
+/// Why `Field::set_size` refused a size.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SizeError {
+    TooNarrow,
+    NoRows,
+    TooFewHidden,
+}
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SizeError::TooNarrow => "figures need at least 4 columns",
+            SizeError::NoRows => "the field needs at least 1 visible row",
+            SizeError::TooFewHidden => "figures spawn in 2 hidden rows",
+        })
+    }
+}
+
+impl std::error::Error for SizeError {}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum FieldState {
+    Playing,
+    Paused,
+    GameOver,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum InputField {
+    Left,
+    Right,
+    Rotate,
+    RotateCcw,
+    Rotate180,
+    SoftDrop,
+    Hold,
+}
+
+/// Everything the player can ask for, as sampled by the frontend.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Input {
+    InputField(InputField),
+    /// Hard drop.
+    Down,
+    Pause,
+    Quit,
+}
+
+/// Inputs sampled between ticks, stamped with the tick they were sampled
+/// for and handed out by the game loop once per tick.
+///
+/// After a lock the next figure waits `spawn_delay` ticks before it may
+/// move. Rotations and holds made meanwhile are applied the moment it
+/// starts moving, as initial rotation and initial hold, followed by the
+/// shifts and drops made meanwhile in the order they came in. Inputs
+/// after a hard drop wait for the figure after that.
+pub struct InputQueue {
+    queue: VecDeque<(u64, Input)>,
+    spawn_delay: u32,
+    /// Ticks left until the figure may move.
+    delay_left: u32,
+    initial_rotation: Option<InputField>,
+    initial_hold: bool,
+    /// Shifts and drops made during the spawn delay.
+    buffered: VecDeque<(u64, Input)>,
+}
+
+impl InputQueue {
+    pub fn new(spawn_delay: u32) -> Self {
+        InputQueue {
+            queue: VecDeque::new(),
+            spawn_delay,
+            delay_left: 0,
+            initial_rotation: None,
+            initial_hold: false,
+            buffered: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, tick: u64, input: Input) {
+        self.queue.push_back((tick, input));
+    }
+
+    /// Starts the spawn delay, to be called when a figure locked.
+    pub fn figure_locked(&mut self) {
+        self.delay_left = self.spawn_delay;
+    }
+
+    /// The spawn delay is running, gravity and shifts should wait.
+    pub fn spawning(&self) -> bool {
+        self.delay_left > 0
+    }
+
+    /// Hands the inputs sampled up to `tick` to `apply`, oldest first.
+    /// `apply` returns whether the input locked the figure, which starts
+    /// the spawn delay right away and keeps the inputs still left for the
+    /// next figure.
+    pub fn drain(&mut self, tick: u64, mut apply: impl FnMut(Input) -> bool) {
+        while let Some(&(stamp, input)) = self.queue.front() {
+            if stamp > tick {
+                break;
+            }
+            self.queue.pop_front();
+            if !self.spawning() {
+                if apply(input) {
+                    self.figure_locked();
+                    return;
+                }
+                continue;
+            }
+            match input {
+                Input::InputField(
+                    rotation @ (InputField::Rotate
+                    | InputField::RotateCcw
+                    | InputField::Rotate180),
+                ) => self.initial_rotation = Some(rotation),
+                Input::InputField(InputField::Hold) => {
+                    self.initial_hold = true
+                }
+                Input::Pause | Input::Quit => {
+                    apply(input);
+                }
+                _ => self.buffered.push_back((stamp, input)),
+            }
+        }
+        if self.spawning() {
+            self.delay_left -= 1;
+            if !self.spawning() {
+                if std::mem::take(&mut self.initial_hold) {
+                    apply(Input::InputField(InputField::Hold));
+                }
+                if let Some(rotation) = self.initial_rotation.take() {
+                    apply(Input::InputField(rotation));
+                }
+                while let Some((_, input)) = self.buffered.pop_front() {
+                    if apply(input) {
+                        self.figure_locked();
+                        break;
+                    }
+                }
+                // the rest goes to the next figure, through its own spawn
+                // delay
+                while let Some(entry) = self.buffered.pop_back() {
+                    self.queue.push_front(entry);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Glyph {
+    Color(Color),
+    Number(u8),
+}
+
+impl Field {
+    /// Glyphs of the whole picture row by row: the next queue, the visible
+    /// part of the field and the score column, `width() + 4 * 2` glyphs
+    /// per row and `height()` rows.
+    pub fn draw_array(&self) -> Vec<Glyph> {
+        let columns = self.width + 4 * 2;
+        let mut result =
+            vec![Glyph::Color(Color::Transparent); self.height * columns];
+
+        let paused = self.state == FieldState::Paused;
+
+        for (i, pixel) in result.iter_mut().enumerate() {
+            let x = i % columns;
+            let y = i / columns;
+
+            if x < self.width + 4 && paused {
+                // keep the board hidden so the game can't be studied
+                continue;
+            } else if x < 4 {
+                let slot = y / NEXT_SLOT_HEIGHT;
+                if slot < self.next_len {
+                    if let Some(figure) = self.next_figures.get(slot) {
+                        *pixel = figure.preview(x, y % NEXT_SLOT_HEIGHT);
+                    }
+                }
+            } else if x < self.width + 4 {
+                let id_x = x - 4;
+                let id_y = y + self.hidden;
+                let mut color = self.pieces[id_y][id_x];
+                let shadow_pos = self.get_current_figure_shadow();
+
+                for p in [shadow_pos, self.current_figure_pos] {
+                    if (p.x..p.x + 4).contains(&(id_x as isize))
+                        && (p.y..p.y + 4).contains(&(id_y as isize))
+                    {
+                        let id_x = (id_x as isize - p.x) as usize;
+                        let id_y = (id_y as isize - p.y) as usize;
+
+                        if self
+                            .current_figure
+                            .get_rect(self.current_figure_rotation)[id_y][id_x]
+                            == 1
+                        {
+                            color = if shadow_pos != self.current_figure_pos
+                                && p == shadow_pos
+                            {
+                                Color::Gray
+                            } else {
+                                self.current_figure.color
+                            };
+                        }
+                    }
+                }
+
+                *pixel = Glyph::Color(color);
+            } else {
+                let id_x = x - 4 - self.width;
+                let id_y = y;
+                if id_x < 4 && id_y < 1 {
+                    let i = (3 - id_x) as u32;
+                    *pixel = Glyph::Number(
+                        (self.score / 100_u32.pow(i) % 100) as u8,
+                    )
+                } else if id_x < 4 && (HOLD_ROW..HOLD_ROW + 4).contains(&id_y)
+                {
+                    if let Some(figure) = self.hold_figure {
+                        *pixel = figure.preview(id_x, id_y - HOLD_ROW);
+                    }
+                } else if id_x == 0 && id_y == LEVEL_ROW {
+                    *pixel = Glyph::Number(self.level.min(99) as u8);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_game() {
+        let inputs = [
+            InputField::Left,
+            InputField::Rotate,
+            InputField::Right,
+            InputField::Right,
+            InputField::SoftDrop,
+            InputField::RotateCcw,
+            InputField::Hold,
+            InputField::Rotate180,
+        ];
+        let mut fields = [Field::with_seed(42), Field::with_seed(42)];
+        for field in &mut fields {
+            for i in 0..2000 {
+                field.process_input(inputs[i % inputs.len()]);
+                field.tick();
+                if i % 37 == 0 {
+                    field.drop_figure();
+                }
+            }
+        }
+        let [a, b] = &fields;
+        assert_eq!(a.draw_array(), b.draw_array());
+        assert_eq!(a.score(), b.score());
+        assert_eq!(a.lines(), b.lines());
+        assert_eq!(a.state(), b.state());
+        assert_eq!(a.current(), b.current());
+        assert!(a.next_queue().eq(b.next_queue()));
+        assert_eq!(a.hold(), b.hold());
+    }
+
+    #[test]
+    fn inputs_after_a_hard_drop_wait_for_the_spawn() {
+        let mut queue = InputQueue::new(SPAWN_DELAY);
+        queue.push(0, Input::Down);
+        queue.push(0, Input::InputField(InputField::Left));
+        let mut applied = Vec::new();
+        for tick in 0..=SPAWN_DELAY as u64 {
+            queue.drain(tick, |input| {
+                applied.push((tick, input));
+                input == Input::Down
+            });
+            // the spawn delay starts on the tick of the lock
+            assert!(queue.spawning() || tick == SPAWN_DELAY as u64);
+        }
+        assert_eq!(
+            applied,
+            [
+                (0, Input::Down),
+                (SPAWN_DELAY as u64, Input::InputField(InputField::Left)),
+            ]
+        );
+    }
+
+    /// A fresh field with `rows` stacked at the bottom, `#` for a block,
+    /// and a T about to fall.
+    fn field_with_t(rows: &[&str]) -> Field {
+        let mut field = Field::with_seed(0);
+        let top = field.rows() - rows.len();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    field.pieces[top + y][x] = Color::Gray;
+                }
+            }
+        }
+        field.place_at_spawn(Figure {
+            kind: FigureKind::T,
+            color: FigureKind::T.color(),
+        });
+        field
+    }
+
+    /// Slot for a T-spin double, covered on the left.
+    const TSD: [&str; 3] = ["####......", "###...####", "####.#####"];
+
+    fn spin_into_slot(last: InputField) -> LineClear {
+        let mut field = field_with_t(&TSD);
+        assert!(field.process_input(InputField::Rotate));
+        while field.process_input(InputField::SoftDrop) {}
+        assert!(field.process_input(last));
+        field.drop_figure().expect("the T locks")
+    }
+
+    #[test]
+    fn t_spin_double() {
+        let clear = spin_into_slot(InputField::Rotate);
+        assert_eq!(clear.tspin, TSpin::Full);
+        assert_eq!(clear.lines, 2);
+    }
+
+    #[test]
+    fn t_spin_mini() {
+        // turned back the T sits flat in the slot with one front corner
+        // open
+        let clear = spin_into_slot(InputField::RotateCcw);
+        assert_eq!(clear.tspin, TSpin::Mini);
+        assert_eq!(clear.lines, 1);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::field::FigureKind;
+use rand::{seq::SliceRandom, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Source of the figure sequence. `Field` asks it for one kind every time
+/// the next queue needs refilling. Implementations are serialized with the
+/// field by their type name.
+#[typetag::serde]
+pub trait PieceGenerator {
+    fn next_kind(&mut self, rng: &mut dyn RngCore) -> FigureKind;
+}
+
+/// Every kind is drawn independently with the same probability.
+#[derive(Serialize, Deserialize)]
+pub struct Random;
+
+#[typetag::serde]
+impl PieceGenerator for Random {
+    fn next_kind(&mut self, rng: &mut dyn RngCore) -> FigureKind {
+        rng.gen()
+    }
+}
+
+/// Deals all seven kinds in a shuffled bag before starting a new one, so
+/// the same kind never shows up more than twice in a row and never waits
+/// longer than twelve figures.
+#[derive(Serialize, Deserialize)]
+pub struct SevenBag {
+    bag: Vec<FigureKind>,
+}
+
+impl SevenBag {
+    pub fn new() -> Self {
+        SevenBag { bag: Vec::new() }
+    }
+}
+
+impl Default for SevenBag {
+    fn default() -> Self {
+        SevenBag::new()
+    }
+}
+
+#[typetag::serde]
+impl PieceGenerator for SevenBag {
+    fn next_kind(&mut self, rng: &mut dyn RngCore) -> FigureKind {
+        if self.bag.is_empty() {
+            self.bag = FigureKind::ALL.to_vec();
+            self.bag.shuffle(rng);
+        }
+        self.bag.pop().unwrap()
+    }
+}
+
+/// TGM style randomizer: rerolls a few times when the drawn kind is one
+/// of the last four dealt, and never starts with an S, Z or O.
+#[derive(Serialize, Deserialize)]
+pub struct History {
+    history: VecDeque<FigureKind>,
+    rolls: u32,
+    first: bool,
+}
+
+impl History {
+    /// `rolls` is the number of draws before a repeat is accepted, 4 in
+    /// TGM and 6 in TGM2.
+    pub fn new(rolls: u32) -> Self {
+        History {
+            history: VecDeque::from(vec![
+                FigureKind::PZ,
+                FigureKind::NZ,
+                FigureKind::NZ,
+                FigureKind::PZ,
+            ]),
+            rolls,
+            first: true,
+        }
+    }
+}
+
+#[typetag::serde]
+impl PieceGenerator for History {
+    fn next_kind(&mut self, rng: &mut dyn RngCore) -> FigureKind {
+        let kind = if self.first {
+            self.first = false;
+            *[
+                FigureKind::Bar,
+                FigureKind::PL,
+                FigureKind::NL,
+                FigureKind::T,
+            ]
+            .choose(rng)
+            .unwrap()
+        } else {
+            let mut kind: FigureKind = rng.gen();
+            for _ in 1..self.rolls {
+                if !self.history.contains(&kind) {
+                    break;
+                }
+                kind = rng.gen();
+            }
+            kind
+        };
+        self.history.pop_front();
+        self.history.push_back(kind);
+        kind
+    }
+}
@@ -182,6 +182,490 @@ const GLYPHS_NUMBERS: [[u8; 16]; 10] = [
   ],
 ];
 
+#[rustfmt::skip]
+const GLYPHS_LETTERS: [[u8; 16]; 26] = [
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00011000,
+    0b00100100,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01111110,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01111100,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01111100,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01111100,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00111100,
+    0b01000010,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000010,
+    0b00111100,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01111000,
+    0b01000100,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000100,
+    0b01111000,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01111110,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01111100,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01111110,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01111110,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01111100,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00111100,
+    0b01000010,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01001110,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b00111100,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01111110,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00111110,
+    0b00001000,
+    0b00001000,
+    0b00001000,
+    0b00001000,
+    0b00001000,
+    0b00001000,
+    0b00001000,
+    0b00001000,
+    0b00111110,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00011110,
+    0b00000010,
+    0b00000010,
+    0b00000010,
+    0b00000010,
+    0b00000010,
+    0b00000010,
+    0b01000010,
+    0b01000010,
+    0b00111100,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01000010,
+    0b01000100,
+    0b01001000,
+    0b01010000,
+    0b01100000,
+    0b01010000,
+    0b01001000,
+    0b01000100,
+    0b01000010,
+    0b01000010,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01111110,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01000010,
+    0b01100110,
+    0b01011010,
+    0b01011010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01000010,
+    0b01100010,
+    0b01100010,
+    0b01010010,
+    0b01010010,
+    0b01001010,
+    0b01001010,
+    0b01000110,
+    0b01000110,
+    0b01000010,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00111100,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b00111100,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01111100,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01111100,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b01000000,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00111100,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01001010,
+    0b01000100,
+    0b00111010,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01111100,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01111100,
+    0b01010000,
+    0b01001000,
+    0b01000100,
+    0b01000010,
+    0b01000010,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00111100,
+    0b01000010,
+    0b01000000,
+    0b01000000,
+    0b00111100,
+    0b00000010,
+    0b00000010,
+    0b00000010,
+    0b01000010,
+    0b00111100,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01111110,
+    0b00010000,
+    0b00010000,
+    0b00010000,
+    0b00010000,
+    0b00010000,
+    0b00010000,
+    0b00010000,
+    0b00010000,
+    0b00010000,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b00111100,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b00100100,
+    0b00100100,
+    0b00100100,
+    0b00011000,
+    0b00011000,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01000010,
+    0b01011010,
+    0b01011010,
+    0b01011010,
+    0b01100110,
+    0b01000010,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01000010,
+    0b01000010,
+    0b00100100,
+    0b00100100,
+    0b00011000,
+    0b00011000,
+    0b00100100,
+    0b00100100,
+    0b01000010,
+    0b01000010,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01000010,
+    0b01000010,
+    0b00100100,
+    0b00100100,
+    0b00011000,
+    0b00010000,
+    0b00010000,
+    0b00010000,
+    0b00010000,
+    0b00010000,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+  [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b01111110,
+    0b00000010,
+    0b00000100,
+    0b00000100,
+    0b00001000,
+    0b00010000,
+    0b00100000,
+    0b00100000,
+    0b01000000,
+    0b01111110,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+  ],
+];
+
+/// Bitmap of `c`, 8 pixels wide and 16 high like the digits. Letters of
+/// either case and digits have one, anything else is drawn blank.
+pub fn glyph(c: char) -> Option<&'static [u8; 16]> {
+    match c {
+        '0'..='9' => Some(&GLYPHS_NUMBERS[c as usize - '0' as usize]),
+        'a'..='z' => Some(&GLYPHS_LETTERS[c as usize - 'a' as usize]),
+        'A'..='Z' => Some(&GLYPHS_LETTERS[c as usize - 'A' as usize]),
+        _ => None,
+    }
+}
+
+#[allow(clippy::needless_range_loop)]
 pub fn glyph_x2(i: u32) -> [[bool; 16]; 16] {
     assert!(i < 100);
     let left = i / 10;
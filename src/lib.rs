@@ -0,0 +1,16 @@
+//! Headless tetris engine. The windowed game in `main.rs` is built on top
+//! of it behind the `gui` feature.
+
+pub mod autoshift;
+pub mod field;
+pub mod generator;
+pub mod palette;
+pub mod replay;
+pub mod scoring;
+
+pub use crate::field::{
+    Color, Field, FieldState, FigureKind, GameEvent, Glyph, GravityCurve,
+    Input, InputField, InputQueue, Placement, Rotation, SizeError,
+    FIELD_HEIGHT, FIELD_WIDTH, HIDDEN_ROWS, SPAWN_DELAY, TICK,
+    TICKS_PER_SECOND,
+};
@@ -1,87 +1,422 @@
 use pixels::{Pixels, SurfaceTexture};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 use winit::{
-    dpi::{PhysicalSize, Size},
+    dpi::{LogicalSize, PhysicalSize},
     event::{
         ElementState, Event, KeyboardInput, StartCause, VirtualKeyCode,
         WindowEvent,
     },
     event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    window::{Fullscreen, Window, WindowBuilder},
 };
 
+use tetris::autoshift::{self, AutoShift};
+use tetris::generator::{History, PieceGenerator, Random};
+use tetris::palette::Palette;
+use tetris::replay::{Action, Player, Replay};
+use tetris::scoring::Classic;
+use tetris::{
+    Field, FieldState, GameEvent, Glyph, GravityCurve, Input, InputField,
+    InputQueue, SPAWN_DELAY, TICK,
+};
+
+mod bindings;
+use bindings::{Bindings, Control, Rebind, REBIND_KEY};
+
 mod glyphs;
 use glyphs::glyph_x2;
-mod tetris;
-use tetris::{Field, Glyph, InputField, FIELD_HEIGHT, FIELD_WIDTH};
 
 const PIECE_DRAW_SIZE: usize = 16;
-const WIDTH: usize = (FIELD_WIDTH + 4 * 2) * PIECE_DRAW_SIZE;
-const HEIGHT: usize = FIELD_HEIGHT * PIECE_DRAW_SIZE;
-
-const PERIOD_MS: u32 = 200;
-
-impl Field {
-    pub fn draw(&self, frame: &mut [u8]) {
-        let array = self.draw_array();
-        for (i, pixel) in frame.chunks_exact_mut(4).enumerate() {
-            let x = i % WIDTH;
-            let y = i / WIDTH;
-
-            let id_x = x / (WIDTH / (FIELD_WIDTH + 4 * 2));
-            let id_y = y / (HEIGHT / FIELD_HEIGHT);
-
-            if id_y * (FIELD_WIDTH + 4 * 2) + id_x
-                < (FIELD_WIDTH + 4 * 2) * FIELD_HEIGHT
-            {
-                let glyph = &array[id_y * (FIELD_WIDTH + 4 * 2) + id_x];
-                match glyph {
-                    Glyph::Color(c) => {
-                        let c: [u8; 4] = (*c).into();
-                        pixel.copy_from_slice(&c);
-                    }
-                    Glyph::Number(n) => {
-                        let loc_x = x - id_x * PIECE_DRAW_SIZE;
-                        let loc_y = y - id_y * PIECE_DRAW_SIZE;
-                        if glyph_x2(*n as u32)[loc_y][loc_x] {
-                            pixel.copy_from_slice(&[!0, !0, !0, !0]);
-                        } else {
-                            pixel.copy_from_slice(&[0, 0, 0, !0]);
-                        }
+
+/// Longest stretch of ticks run at once to catch up after a stall.
+const MAX_CATCH_UP: Duration = Duration::from_millis(250);
+
+/// Size of the picture of `field` in pixels.
+fn draw_size(field: &Field) -> (usize, usize) {
+    (
+        (field.width() + 4 * 2) * PIECE_DRAW_SIZE,
+        field.height() * PIECE_DRAW_SIZE,
+    )
+}
+
+fn draw(field: &Field, frame: &mut [u8], palette: &Palette) {
+    let array = field.draw_array();
+    let columns = field.width() + 4 * 2;
+    let (width, _) = draw_size(field);
+    for (i, pixel) in frame.chunks_exact_mut(4).enumerate() {
+        let x = i % width;
+        let y = i / width;
+
+        let id_x = x / PIECE_DRAW_SIZE;
+        let id_y = y / PIECE_DRAW_SIZE;
+
+        if id_y * columns + id_x < array.len() {
+            let glyph = &array[id_y * columns + id_x];
+            match glyph {
+                Glyph::Color(c) => {
+                    pixel.copy_from_slice(&palette.rgba(*c));
+                }
+                Glyph::Number(n) => {
+                    let loc_x = x - id_x * PIECE_DRAW_SIZE;
+                    let loc_y = y - id_y * PIECE_DRAW_SIZE;
+                    if glyph_x2(*n as u32)[loc_y][loc_x] {
+                        pixel.copy_from_slice(&[!0, !0, !0, !0]);
+                    } else {
+                        pixel.copy_from_slice(&[0, 0, 0, !0]);
                     }
                 }
             }
-            if x == 4 * PIECE_DRAW_SIZE
-                || x == (4 + FIELD_WIDTH) * PIECE_DRAW_SIZE - 1
-            {
-                pixel.copy_from_slice(&[!0, !0, !0, !0]);
+        }
+        if x == 4 * PIECE_DRAW_SIZE
+            || x == (4 + field.width()) * PIECE_DRAW_SIZE - 1
+        {
+            pixel.copy_from_slice(&[!0, !0, !0, !0]);
+        }
+    }
+}
+
+/// Draws `lines` centered on a black band across the middle of the frame,
+/// breaking lines too long for it at spaces.
+fn draw_text(field: &Field, frame: &mut [u8], lines: &[String]) {
+    const GLYPH_WIDTH: usize = 8;
+    const GLYPH_HEIGHT: usize = 16;
+    let (width, height) = draw_size(field);
+    let columns = width / GLYPH_WIDTH - 2;
+    let mut rows: Vec<String> = Vec::new();
+    for line in lines {
+        let mut row = String::new();
+        for word in line.split_whitespace() {
+            if !row.is_empty() && row.len() + 1 + word.len() > columns {
+                rows.push(std::mem::take(&mut row));
+            }
+            if !row.is_empty() {
+                row.push(' ');
+            }
+            row.push_str(word);
+        }
+        rows.push(row);
+    }
+
+    let band = (rows.len() + 1) * GLYPH_HEIGHT;
+    let top = height.saturating_sub(band) / 2;
+    for (i, pixel) in frame.chunks_exact_mut(4).enumerate() {
+        let x = i % width;
+        let y = i / width;
+        if y < top || y >= top + band {
+            continue;
+        }
+        let mut lit = false;
+        let text_y = y - top;
+        if text_y >= GLYPH_HEIGHT / 2 {
+            let row = (text_y - GLYPH_HEIGHT / 2) / GLYPH_HEIGHT;
+            let loc_y = (text_y - GLYPH_HEIGHT / 2) % GLYPH_HEIGHT;
+            if let Some(row) = rows.get(row) {
+                let length = row.chars().count().min(columns) * GLYPH_WIDTH;
+                let left = (width - length) / 2;
+                if x >= left && x < left + length {
+                    let glyph = row.chars().nth((x - left) / GLYPH_WIDTH);
+                    let loc_x = (x - left) % GLYPH_WIDTH;
+                    lit = glyph.and_then(glyphs::glyph).is_some_and(|bits| {
+                        (bits[loc_y] >> (7 - loc_x)) % 2 == 1
+                    });
+                }
             }
         }
+        if lit || y == top || y == top + band - 1 {
+            pixel.copy_from_slice(&[!0, !0, !0, !0]);
+        } else {
+            pixel.copy_from_slice(&[0, 0, 0, !0]);
+        }
     }
 }
 
-fn main() {
-    let event_loop = EventLoop::new();
+/// Text drawn over a field that isn't being played.
+fn state_text(state: FieldState) -> Option<&'static str> {
+    match state {
+        FieldState::Playing => None,
+        FieldState::Paused => Some("paused"),
+        FieldState::GameOver => Some("game over"),
+    }
+}
+
+/// Value following the command line argument `name`.
+fn arg(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != name);
+    args.next();
+    args.next()
+}
+
+/// Options that change how the field plays, stored in replays so they can
+/// build the same field again.
+const SETTINGS: [&str; 7] = [
+    "--randomizer",
+    "--gravity",
+    "--scoring",
+    "--next",
+    "--width",
+    "--height",
+    "--hidden",
+];
+
+/// Builds a field with the randomizer picked by
+/// `--randomizer random|history`, the 7-bag one otherwise, and the seed
+/// given by `--seed`, a random one otherwise. `--gravity nes` switches to
+/// the NES speed curve, `--scoring classic` to the original scoring and
+/// `--next N` shows N upcoming figures. `--width`, `--height` and
+/// `--hidden` set the size of the field. Options are looked up with
+/// `option`.
+fn new_field(option: &dyn Fn(&str) -> Option<String>) -> Field {
+    let seed = option("--seed")
+        .map(|seed| seed.parse().expect("--seed takes an unsigned number"));
+    let generator: Option<Box<dyn PieceGenerator>> =
+        match option("--randomizer").as_deref() {
+            Some("random") => Some(Box::new(Random)),
+            Some("history") => Some(Box::new(History::new(6))),
+            _ => None,
+        };
+    let mut field = match generator {
+        Some(generator) => {
+            Field::with_generator(generator, seed.unwrap_or_else(rand::random))
+        }
+        None => seed.map_or_else(Field::new, Field::with_seed),
+    };
+    if option("--gravity").as_deref() == Some("nes") {
+        field.set_gravity_curve(GravityCurve::Nes);
+    }
+    if option("--scoring").as_deref() == Some("classic") {
+        field.set_scoring_rule(Box::new(Classic));
+    }
+    if let Some(len) = option("--next") {
+        field.set_next_len(len.parse().expect("--next takes a number"));
+    }
+    let size = |name: &str, default: usize| {
+        option(name).map_or(default, |size| {
+            size.parse()
+                .unwrap_or_else(|_| panic!("{} takes a number", name))
+        })
+    };
+    let resized = field.set_size(
+        size("--width", field.width()),
+        size("--height", field.height()),
+        size("--hidden", field.hidden()),
+    );
+    if let Err(e) = resized {
+        eprintln!("can't play on that field: {}", e);
+        std::process::exit(1);
+    }
+    field
+}
+
+/// Picks the palette given by `--palette classic|colorblind`, the
+/// guideline one otherwise.
+fn palette_from_args() -> Palette {
+    match arg("--palette").as_deref() {
+        Some("classic") => Palette::CLASSIC,
+        Some("colorblind") => Palette::COLORBLIND,
+        _ => Palette::GUIDELINE,
+    }
+}
+
+/// Opens a window of the picture of `field` in logical pixels, so it
+/// starts twice as large on a display scaled by 2. The picture is drawn
+/// into a buffer of fixed size and scaled up by whole numbers to fill the
+/// window, with black bars around it.
+fn open_window(event_loop: &EventLoop<()>, field: &Field) -> (Window, Pixels) {
+    let (width, height) = draw_size(field);
+    let size = LogicalSize::new(width as u32, height as u32);
     let window = WindowBuilder::new()
-        .with_resizable(false)
         .with_title("tetris")
-        .with_inner_size(Size::Physical(PhysicalSize {
-            width: WIDTH as u32,
-            height: HEIGHT as u32,
-        }))
-        .build(&event_loop)
+        .with_inner_size(size)
+        .with_min_inner_size(size)
+        .build(event_loop)
         .unwrap();
 
     let window_size = window.inner_size();
     let surface_texture =
         SurfaceTexture::new(window_size.width, window_size.height, &window);
-    let mut pixels =
-        Pixels::new(window_size.width, window_size.height, surface_texture)
-            .unwrap();
+    let pixels =
+        Pixels::new(width as u32, height as u32, surface_texture).unwrap();
+    (window, pixels)
+}
 
-    let period = Duration::new(1, 0) / 1000 * PERIOD_MS;
+/// Fits the surface to a window of `size` physical pixels.
+fn resize(pixels: &mut Pixels, size: PhysicalSize<u32>) {
+    // a minimized window has no size to fit to
+    if size.width > 0 && size.height > 0 {
+        pixels.resize_surface(size.width, size.height);
+    }
+}
+
+fn toggle_fullscreen(window: &Window) {
+    if window.fullscreen().is_some() {
+        window.set_fullscreen(None);
+    } else {
+        window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
+}
+
+/// A running game and the recording of everything done to its field.
+#[derive(Serialize, Deserialize)]
+struct Game {
+    field: Field,
+    replay: Replay,
+    /// Time played in earlier runs of a resumed game.
+    played: Duration,
+    #[serde(skip, default = "Instant::now")]
+    start: Instant,
+}
+
+/// Where an unfinished game is kept between launches.
+fn save_path() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("tetris").join("save.json"))
+}
+
+impl Game {
+    fn new() -> Self {
+        let field = new_field(&arg);
+        let settings = SETTINGS
+            .iter()
+            .filter_map(|name| Some((name.to_string(), arg(name)?)))
+            .collect();
+        Game {
+            replay: Replay::new(field.seed(), settings),
+            field,
+            played: Duration::ZERO,
+            start: Instant::now(),
+        }
+    }
 
-    let mut field = Field::new();
+    fn act(&mut self, action: Action) {
+        self.replay
+            .record(self.played + self.start.elapsed(), action);
+        action.apply(&mut self.field);
+    }
+
+    /// Like `act` for inputs, but only inputs that moved the figure are
+    /// recorded, the others do nothing on playback either.
+    fn input(&mut self, input: InputField) -> bool {
+        let time = self.played + self.start.elapsed();
+        let moved = self.field.process_input(input);
+        if moved {
+            self.replay.record(time, Action::Input(input));
+        }
+        moved
+    }
+
+    /// Picks up the game saved on the last exit, paused. The save is
+    /// removed, so a game can only be resumed once.
+    fn resume() -> Option<Self> {
+        let path = save_path()?;
+        let file = File::open(&path).ok()?;
+        let game: serde_json::Result<Game> =
+            serde_json::from_reader(BufReader::new(file));
+        let _ = fs::remove_file(&path);
+        let mut game = game
+            .map_err(|e| eprintln!("can't resume {}: {}", path.display(), e))
+            .ok()?;
+        if game.field.state() == FieldState::Playing {
+            game.act(Action::Pause);
+        }
+        Some(game)
+    }
+
+    /// Saves the game for the next launch unless it is over.
+    fn save(&mut self) {
+        if self.field.state() == FieldState::GameOver {
+            return;
+        }
+        self.played += self.start.elapsed();
+        self.start = Instant::now();
+        let path = match save_path() {
+            Some(path) => path,
+            None => return,
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| File::create(&path))
+            .and_then(|file| {
+                let mut w = BufWriter::new(file);
+                serde_json::to_writer(&mut w, self)?;
+                w.flush()
+            });
+        if let Err(e) = result {
+            eprintln!("can't save game {}: {}", path.display(), e);
+        }
+    }
+
+    /// Writes the recording to the file given by `--record`, if any.
+    fn save_replay(&self) {
+        if let Some(path) = arg("--record") {
+            let result = File::create(&path).and_then(|file| {
+                let mut w = BufWriter::new(file);
+                self.replay.write_to(&mut w)?;
+                w.flush()
+            });
+            if let Err(e) = result {
+                eprintln!("can't write replay {}: {}", path, e);
+            }
+        }
+    }
+}
+
+fn title(state: FieldState) -> &'static str {
+    match state {
+        FieldState::Playing => "tetris",
+        FieldState::Paused => "tetris - paused",
+        FieldState::GameOver => "tetris - game over",
+    }
+}
+
+fn main() {
+    if let Some(path) = arg("--replay") {
+        play_replay(&path);
+    }
+    // an unfinished game from the last time is resumed unless `--new` is
+    // given
+    let resumed = if std::env::args().any(|arg| arg == "--new") {
+        None
+    } else {
+        Game::resume()
+    };
+    let mut game = resumed.unwrap_or_else(Game::new);
+    let palette = palette_from_args();
+    // `--das`, `--arr`, `--sdr` and `--spawn-delay` are given in ticks,
+    // `--arr 0` shifts straight to the wall
+    let ticks = |name: &str, default: u32| {
+        arg(name).map_or(default, |ticks| {
+            ticks
+                .parse()
+                .unwrap_or_else(|_| panic!("{} takes a number of ticks", name))
+        })
+    };
+    let mut autoshift = AutoShift::new(
+        ticks("--das", autoshift::DAS),
+        ticks("--arr", autoshift::ARR),
+        ticks("--sdr", autoshift::SDR),
+    );
+    let spawn_delay = ticks("--spawn-delay", SPAWN_DELAY);
+    let mut queue = InputQueue::new(spawn_delay);
+    // the field runs on fixed ticks, the picture is redrawn `--fps` times
+    // per second on its own
+    let fps = arg("--fps").map_or(60, |fps| {
+        fps.parse().expect("--fps takes a number of frames")
+    });
+    let frame = Duration::from_secs(1) / fps;
+    let mut tick = 0;
+    let mut next_tick = Instant::now();
+    let mut next_frame = Instant::now();
+
+    let mut bindings = Bindings::load();
+    let mut rebind: Option<Rebind> = None;
+
+    let event_loop = EventLoop::new();
+    let (window, mut pixels) = open_window(&event_loop, &game.field);
+    window.set_title(title(game.field.state()));
 
     event_loop.run(move |event, _, control_flow| match event {
         Event::WindowEvent { event, window_id }
@@ -89,8 +424,36 @@ fn main() {
         {
             match event {
                 WindowEvent::CloseRequested => {
+                    game.save_replay();
+                    game.save();
                     *control_flow = ControlFlow::Exit
                 }
+                WindowEvent::Resized(size) => {
+                    resize(&mut pixels, size);
+                    window.request_redraw();
+                }
+                WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                    resize(&mut pixels, *new_inner_size);
+                    window.request_redraw();
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Released,
+                            virtual_keycode: Some(keycode),
+                            ..
+                        },
+                    ..
+                } => match bindings.control(keycode) {
+                    Some(Control::Left) => autoshift.release(InputField::Left),
+                    Some(Control::Right) => {
+                        autoshift.release(InputField::Right)
+                    }
+                    Some(Control::SoftDrop) => {
+                        autoshift.release(InputField::SoftDrop)
+                    }
+                    _ => {}
+                },
                 WindowEvent::KeyboardInput {
                     input:
                         KeyboardInput {
@@ -99,41 +462,328 @@ fn main() {
                             ..
                         },
                     ..
-                } => match keycode {
-                    VirtualKeyCode::Left => {
-                        field.process_input(InputField::Left);
-                        window.request_redraw();
+                } => {
+                    if let Some(screen) = &mut rebind {
+                        if keycode != REBIND_KEY {
+                            screen.press(keycode);
+                            window.set_title(&screen.prompt());
+                        } else if let Some(new) = screen.next() {
+                            new.save();
+                            bindings = new;
+                            rebind = None;
+                            window.set_title(title(game.field.state()));
+                        } else {
+                            window.set_title(&screen.prompt());
+                        }
+                        return;
                     }
-                    VirtualKeyCode::Right => {
-                        field.process_input(InputField::Right);
-                        window.request_redraw();
+                    if keycode == REBIND_KEY {
+                        if game.field.state() == FieldState::Playing {
+                            game.act(Action::Pause);
+                            *control_flow = ControlFlow::Wait;
+                            window.request_redraw();
+                        }
+                        let screen = Rebind::new(bindings.clone());
+                        window.set_title(&screen.prompt());
+                        rebind = Some(screen);
+                        return;
                     }
-                    VirtualKeyCode::Up => {
-                        field.process_input(InputField::Rotate);
-                        window.request_redraw();
+                    let control = match bindings.control(keycode) {
+                        Some(control) => control,
+                        None => return,
+                    };
+                    let input = match control {
+                        Control::Left | Control::Right => {
+                            let shift = if control == Control::Left {
+                                InputField::Left
+                            } else {
+                                InputField::Right
+                            };
+                            // key repeat of the system, the auto shift
+                            // takes care of held keys
+                            if !autoshift.press(shift) {
+                                return;
+                            }
+                            Input::InputField(shift)
+                        }
+                        Control::Rotate => {
+                            Input::InputField(InputField::Rotate)
+                        }
+                        Control::RotateCcw => {
+                            Input::InputField(InputField::RotateCcw)
+                        }
+                        Control::Rotate180 => {
+                            Input::InputField(InputField::Rotate180)
+                        }
+                        Control::SoftDrop => {
+                            // held down it keeps dropping on its own
+                            if !autoshift.press(InputField::SoftDrop) {
+                                return;
+                            }
+                            Input::InputField(InputField::SoftDrop)
+                        }
+                        Control::Hold => Input::InputField(InputField::Hold),
+                        Control::HardDrop => Input::Down,
+                        Control::Pause => Input::Pause,
+                        Control::Quit => Input::Quit,
+                        Control::Restart
+                            if game.field.state() == FieldState::GameOver =>
+                        {
+                            game = Game::new();
+                            queue = InputQueue::new(spawn_delay);
+                            window.set_title("tetris");
+                            next_tick = Instant::now();
+                            *control_flow = ControlFlow::WaitUntil(next_tick);
+                            window.request_redraw();
+                            return;
+                        }
+                        Control::Restart => return,
+                        Control::Fullscreen => {
+                            toggle_fullscreen(&window);
+                            return;
+                        }
+                    };
+                    queue.push(tick, input);
+                    // ticks stop while paused or after the game is over
+                    next_tick = next_tick.max(Instant::now());
+                    *control_flow = ControlFlow::WaitUntil(next_tick);
+                }
+                _ => {}
+            }
+        }
+        Event::NewEvents(
+            StartCause::Init | StartCause::ResumeTimeReached { .. },
+        ) => {
+            let state = game.field.state();
+            let now = Instant::now();
+            // after a stall the ticks beyond the last quarter second are
+            // dropped rather than raced through
+            if let Some(oldest) = now.checked_sub(MAX_CATCH_UP) {
+                next_tick = next_tick.max(oldest);
+            }
+            let mut quit = false;
+            while next_tick <= now {
+                queue.drain(tick, |input| match input {
+                    Input::InputField(input) => {
+                        game.input(input);
+                        false
                     }
-                    VirtualKeyCode::Down => {
-                        field.drop_figure();
-                        window.request_redraw();
+                    Input::Down => {
+                        // a hard drop always locks while playing
+                        let locks = game.field.state() == FieldState::Playing;
+                        game.act(Action::HardDrop);
+                        locks
                     }
-                    VirtualKeyCode::Escape => {
-                        *control_flow = ControlFlow::Exit;
+                    Input::Pause => {
+                        game.act(Action::Pause);
+                        false
                     }
-                    _ => {}
-                },
-                _ => {}
+                    Input::Quit => {
+                        quit = true;
+                        false
+                    }
+                });
+                autoshift.tick(|input| !queue.spawning() && game.input(input));
+                // gravity and lock delay wait for the figure to spawn
+                if !queue.spawning()
+                    && game.field.state() == FieldState::Playing
+                {
+                    game.act(Action::Ticks(1));
+                }
+                // locks by gravity start the spawn delay here, hard drops
+                // started it in `drain` already
+                for event in game.field.drain_events() {
+                    if let GameEvent::Locked { .. } = event {
+                        queue.figure_locked();
+                    }
+                }
+                tick += 1;
+                next_tick += TICK;
+            }
+            if quit {
+                game.save_replay();
+                game.save();
+                *control_flow = ControlFlow::Exit;
+                return;
+            }
+            if game.field.state() != state {
+                if game.field.state() == FieldState::GameOver {
+                    game.save_replay();
+                }
+                window.set_title(title(game.field.state()));
+            }
+            if next_frame <= now || game.field.state() != FieldState::Playing {
+                window.request_redraw();
+                next_frame = (next_frame + frame).max(now);
+            }
+            *control_flow = match game.field.state() {
+                FieldState::Playing => {
+                    ControlFlow::WaitUntil(next_tick.min(next_frame))
+                }
+                FieldState::Paused | FieldState::GameOver => ControlFlow::Wait,
+            };
+        }
+        Event::RedrawRequested(_) => {
+            draw(&game.field, pixels.get_frame(), &palette);
+            if let Some(screen) = &rebind {
+                draw_text(&game.field, pixels.get_frame(), &screen.lines());
+            } else if let Some(text) = state_text(game.field.state()) {
+                draw_text(&game.field, pixels.get_frame(), &[text.into()]);
+            }
+            if pixels.render().is_err() {
+                *control_flow = ControlFlow::Exit;
+            }
+        }
+        _ => {}
+    });
+}
+
+/// Plays back a recorded game as fast as it was played. While watching,
+/// 1 and 2 set the speed, F switches to frame by frame and N then shows
+/// the next action.
+struct Playback {
+    player: Player,
+    /// `None` plays frame by frame.
+    speed: Option<u32>,
+    /// Replay time reached so far.
+    clock: Duration,
+    last: Instant,
+}
+
+impl Playback {
+    /// Catches `field` up with the wall clock and tells when to come back.
+    fn advance(&mut self, field: &mut Field) -> ControlFlow {
+        let now = Instant::now();
+        if let Some(speed) = self.speed {
+            self.clock += (now - self.last) * speed;
+            self.player.play_until(field, self.clock);
+        }
+        self.last = now;
+        match (self.speed, self.player.next_time()) {
+            (Some(speed), Some(time)) => {
+                ControlFlow::WaitUntil(now + (time - self.clock) / speed)
             }
+            _ => ControlFlow::Wait,
+        }
+    }
+
+    fn set_speed(
+        &mut self,
+        field: &mut Field,
+        speed: Option<u32>,
+    ) -> ControlFlow {
+        self.advance(field);
+        self.speed = speed;
+        self.advance(field)
+    }
+
+    /// Applies a single action, in frame by frame mode.
+    fn step(&mut self, field: &mut Field) {
+        if let Some(time) = self.player.next_time() {
+            self.player.step(field);
+            self.clock = time;
         }
-        Event::NewEvents(StartCause::Init) => {
-            *control_flow = ControlFlow::WaitUntil(Instant::now() + period)
+    }
+
+    fn title(&self) -> String {
+        match self.speed {
+            Some(speed) => format!("tetris - replay {}x", speed),
+            None => "tetris - replay frame by frame".to_string(),
         }
-        Event::NewEvents(StartCause::ResumeTimeReached { .. }) => {
-            *control_flow = ControlFlow::WaitUntil(Instant::now() + period);
-            field.make_step();
+    }
+}
+
+fn play_replay(path: &str) -> ! {
+    let replay = File::open(path)
+        .and_then(|file| Replay::read_from(&mut BufReader::new(file)))
+        .unwrap_or_else(|e| {
+            eprintln!("can't read replay {}: {}", path, e);
+            std::process::exit(1)
+        });
+    let mut field = new_field(&|name| match name {
+        "--seed" => Some(replay.seed.to_string()),
+        _ => replay.setting(name).map(str::to_string),
+    });
+    let mut playback = Playback {
+        player: Player::new(replay),
+        speed: Some(1),
+        clock: Duration::ZERO,
+        last: Instant::now(),
+    };
+    let palette = palette_from_args();
+    let bindings = Bindings::load();
+
+    let event_loop = EventLoop::new();
+    let (window, mut pixels) = open_window(&event_loop, &field);
+    window.set_title(&playback.title());
+
+    event_loop.run(move |event, _, control_flow| match event {
+        Event::WindowEvent { event, window_id }
+            if window_id == window.id() =>
+        {
+            match event {
+                WindowEvent::CloseRequested => {
+                    *control_flow = ControlFlow::Exit
+                }
+                WindowEvent::Resized(size) => {
+                    resize(&mut pixels, size);
+                    window.request_redraw();
+                }
+                WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                    resize(&mut pixels, *new_inner_size);
+                    window.request_redraw();
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(keycode),
+                            ..
+                        },
+                    ..
+                } => {
+                    let speed = match keycode {
+                        _ if bindings.control(keycode)
+                            == Some(Control::Fullscreen) =>
+                        {
+                            toggle_fullscreen(&window);
+                            return;
+                        }
+                        VirtualKeyCode::Key1 => Some(1),
+                        VirtualKeyCode::Key2 => Some(2),
+                        VirtualKeyCode::F => None,
+                        VirtualKeyCode::N if playback.speed.is_none() => {
+                            playback.step(&mut field);
+                            window.request_redraw();
+                            return;
+                        }
+                        VirtualKeyCode::Escape => {
+                            *control_flow = ControlFlow::Exit;
+                            return;
+                        }
+                        _ => return,
+                    };
+                    *control_flow = playback.set_speed(&mut field, speed);
+                    window.set_title(&playback.title());
+                    window.request_redraw();
+                }
+                _ => {}
+            }
+        }
+        Event::NewEvents(
+            StartCause::Init | StartCause::ResumeTimeReached { .. },
+        ) => {
+            *control_flow = playback.advance(&mut field);
             window.request_redraw();
         }
+        Event::MainEventsCleared => {
+            field.drain_events().for_each(drop);
+        }
         Event::RedrawRequested(_) => {
-            field.draw(pixels.get_frame());
+            draw(&field, pixels.get_frame(), &palette);
+            if let Some(text) = state_text(field.state()) {
+                draw_text(&field, pixels.get_frame(), &[text.into()]);
+            }
             if pixels.render().is_err() {
                 *control_flow = ControlFlow::Exit;
             }
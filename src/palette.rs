@@ -0,0 +1,71 @@
+use crate::field::Color;
+
+/// RGBA values the colors of the field are drawn with.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Palette {
+    pub transparent: [u8; 4],
+    pub red: [u8; 4],
+    pub green: [u8; 4],
+    pub blue: [u8; 4],
+    pub cyan: [u8; 4],
+    pub magenta: [u8; 4],
+    pub yellow: [u8; 4],
+    pub orange: [u8; 4],
+    pub gray: [u8; 4],
+}
+
+impl Palette {
+    /// Fully saturated colors, as the game was first drawn.
+    pub const CLASSIC: Palette = Palette {
+        transparent: [0, 0, 0, !0],
+        red: [!0, 0, 0, !0],
+        green: [0, !0, 0, !0],
+        blue: [0, 0, !0, !0],
+        cyan: [0, !0, !0, !0],
+        magenta: [!0, 0, !0, !0],
+        yellow: [!0, !0, 0, !0],
+        orange: [!0, !0 / 2, 0, !0],
+        gray: [!0 / 3, !0 / 3, !0 / 3, !0],
+    };
+
+    /// The colors of the Tetris guideline.
+    pub const GUIDELINE: Palette = Palette {
+        transparent: [0, 0, 0, !0],
+        red: [0xf0, 0x00, 0x00, !0],
+        green: [0x00, 0xf0, 0x00, !0],
+        blue: [0x00, 0x00, 0xf0, !0],
+        cyan: [0x00, 0xf0, 0xf0, !0],
+        magenta: [0xa0, 0x00, 0xf0, !0],
+        yellow: [0xf0, 0xf0, 0x00, !0],
+        orange: [0xf0, 0xa0, 0x00, !0],
+        gray: [0x50, 0x50, 0x50, !0],
+    };
+
+    /// The Okabe-Ito colors, which stay apart with all common kinds of
+    /// color blindness.
+    pub const COLORBLIND: Palette = Palette {
+        transparent: [0, 0, 0, !0],
+        red: [0xd5, 0x5e, 0x00, !0],
+        green: [0x00, 0x9e, 0x73, !0],
+        blue: [0x00, 0x72, 0xb2, !0],
+        cyan: [0x56, 0xb4, 0xe9, !0],
+        magenta: [0xcc, 0x79, 0xa7, !0],
+        yellow: [0xf0, 0xe4, 0x42, !0],
+        orange: [0xe6, 0x9f, 0x00, !0],
+        gray: [0x50, 0x50, 0x50, !0],
+    };
+
+    pub fn rgba(&self, color: Color) -> [u8; 4] {
+        match color {
+            Color::Transparent => self.transparent,
+            Color::Red => self.red,
+            Color::Green => self.green,
+            Color::Blue => self.blue,
+            Color::Cyan => self.cyan,
+            Color::Magenta => self.magenta,
+            Color::Yellow => self.yellow,
+            Color::Orange => self.orange,
+            Color::Gray => self.gray,
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use crate::field::{Field, InputField, TICK};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+const MAGIC: &[u8; 4] = b"TTRP";
+/// Version written to new files. Version 2 added single ticks, version 3
+/// runs of them, older files are still read.
+const VERSION: u16 = 3;
+
+/// Everything that changes a `Field`: the player's inputs and the gravity
+/// steps of the game loop.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Action {
+    Input(InputField),
+    /// A call to `Field::make_step`.
+    Step,
+    /// Calls to `Field::tick`, one `TICK` apart.
+    Ticks(u32),
+    HardDrop,
+    Pause,
+}
+
+impl Action {
+    pub fn apply(self, field: &mut Field) {
+        match self {
+            Action::Input(input) => {
+                field.process_input(input);
+            }
+            Action::Step => {
+                field.make_step();
+            }
+            Action::Ticks(n) => {
+                for _ in 0..n {
+                    field.tick();
+                }
+            }
+            Action::HardDrop => {
+                field.drop_figure();
+            }
+            Action::Pause => field.toggle_pause(),
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            Action::Step => 0,
+            Action::HardDrop => 1,
+            Action::Pause => 2,
+            Action::Input(InputField::Left) => 3,
+            Action::Input(InputField::Right) => 4,
+            Action::Input(InputField::Rotate) => 5,
+            Action::Input(InputField::RotateCcw) => 6,
+            Action::Input(InputField::Rotate180) => 7,
+            Action::Input(InputField::SoftDrop) => 8,
+            Action::Input(InputField::Hold) => 9,
+            Action::Ticks(_) => 11,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Action> {
+        Some(match code {
+            0 => Action::Step,
+            1 => Action::HardDrop,
+            2 => Action::Pause,
+            3 => Action::Input(InputField::Left),
+            4 => Action::Input(InputField::Right),
+            5 => Action::Input(InputField::Rotate),
+            6 => Action::Input(InputField::RotateCcw),
+            7 => Action::Input(InputField::Rotate180),
+            8 => Action::Input(InputField::SoftDrop),
+            9 => Action::Input(InputField::Hold),
+            _ => return None,
+        })
+    }
+}
+
+/// An action and when it happened, counted from the start of the game.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub time: Duration,
+    pub action: Action,
+}
+
+/// A recorded game. Feeding the entries in order to a field built with
+/// the same seed and settings reproduces the game exactly.
+///
+/// The file starts with the magic `TTRP`, a little endian `u16` version,
+/// the `u64` seed and the settings as a `u32` count of name and value
+/// strings, each a `u32` length followed by UTF-8. The entries follow up
+/// to the end of the file, each a `u64` time in milliseconds and an
+/// action byte. A run of ticks has its `u32` count after the action byte.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    /// Whatever else the frontend needs to build the same field, as name
+    /// and value pairs.
+    pub settings: Vec<(String, String)>,
+    pub entries: Vec<Entry>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, settings: Vec<(String, String)>) -> Self {
+        Replay {
+            seed,
+            settings,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds an action at `time`. Ticks in a row go into one entry, at
+    /// the time of the first of them.
+    pub fn record(&mut self, time: Duration, action: Action) {
+        if let (Action::Ticks(n), Some(last)) =
+            (action, self.entries.last_mut())
+        {
+            if let Action::Ticks(run) = &mut last.action {
+                if let Some(sum) = run.checked_add(n) {
+                    *run = sum;
+                    return;
+                }
+            }
+        }
+        self.entries.push(Entry { time, action });
+    }
+
+    pub fn setting(&self, name: &str) -> Option<&str> {
+        self.settings
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        w.write_all(&self.seed.to_le_bytes())?;
+        w.write_all(&(self.settings.len() as u32).to_le_bytes())?;
+        for (name, value) in &self.settings {
+            write_str(w, name)?;
+            write_str(w, value)?;
+        }
+        for entry in &self.entries {
+            w.write_all(&(entry.time.as_millis() as u64).to_le_bytes())?;
+            w.write_all(&[entry.action.code()])?;
+            if let Action::Ticks(n) = entry.action {
+                w.write_all(&n.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_from(r: &mut impl Read) -> io::Result<Replay> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a replay file"));
+        }
+        let mut version = [0; 2];
+        r.read_exact(&mut version)?;
+        let version = u16::from_le_bytes(version);
+        if !(1..=VERSION).contains(&version) {
+            return Err(invalid("unsupported replay version"));
+        }
+        let mut seed = [0; 8];
+        r.read_exact(&mut seed)?;
+        let mut replay = Replay::new(u64::from_le_bytes(seed), Vec::new());
+        for _ in 0..read_u32(r)? {
+            let name = read_str(r)?;
+            let value = read_str(r)?;
+            replay.settings.push((name, value));
+        }
+        let mut entry = [0; 9];
+        loop {
+            // a clean end of file can only come between two entries
+            match r.read(&mut entry[..1])? {
+                0 => break,
+                _ => r.read_exact(&mut entry[1..])?,
+            }
+            let mut time = [0; 8];
+            time.copy_from_slice(&entry[..8]);
+            let action = match (entry[8], version) {
+                (10, 2) => Action::Ticks(1),
+                (11, 3..) => Action::Ticks(read_u32(r)?),
+                (code, _) => Action::from_code(code)
+                    .ok_or_else(|| invalid("unknown replay action"))?,
+            };
+            replay.record(
+                Duration::from_millis(u64::from_le_bytes(time)),
+                action,
+            );
+        }
+        Ok(replay)
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_str(w: &mut impl Write, s: &str) -> io::Result<()> {
+    w.write_all(&(s.len() as u32).to_le_bytes())?;
+    w.write_all(s.as_bytes())
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut n = [0; 4];
+    r.read_exact(&mut n)?;
+    Ok(u32::from_le_bytes(n))
+}
+
+/// Reads a string, growing the buffer only as far as the data goes so a
+/// corrupt length can't allocate gigabytes.
+fn read_str(r: &mut impl Read) -> io::Result<String> {
+    let len = read_u32(r)? as usize;
+    let mut s = Vec::new();
+    if r.take(len as u64).read_to_end(&mut s)? != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(s).map_err(|_| invalid("setting is not UTF-8"))
+}
+
+/// Feeds the entries of a replay to a field one by one, runs of ticks
+/// one tick at a time.
+pub struct Player {
+    entries: Vec<Entry>,
+    next: usize,
+    /// Ticks of the next entry played already.
+    ticked: u32,
+}
+
+impl Player {
+    pub fn new(replay: Replay) -> Self {
+        Player {
+            entries: replay.entries,
+            next: 0,
+            ticked: 0,
+        }
+    }
+
+    /// Time of the action `step` applies next, `None` once all of them
+    /// were played.
+    pub fn next_time(&self) -> Option<Duration> {
+        self.entries
+            .get(self.next)
+            .map(|entry| entry.time + TICK * self.ticked)
+    }
+
+    /// Applies the next action. Returns `false` if there was none left.
+    pub fn step(&mut self, field: &mut Field) -> bool {
+        match self.entries.get(self.next) {
+            Some(&Entry {
+                action: Action::Ticks(n),
+                ..
+            }) if self.ticked + 1 < n => {
+                field.tick();
+                self.ticked += 1;
+                true
+            }
+            Some(&Entry {
+                action: Action::Ticks(n),
+                ..
+            }) => {
+                // the last tick of the run, none for an empty one
+                if n > 0 {
+                    field.tick();
+                }
+                self.next += 1;
+                self.ticked = 0;
+                true
+            }
+            Some(entry) => {
+                entry.action.apply(field);
+                self.next += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Applies all entries up to and including `time`.
+    pub fn play_until(&mut self, field: &mut Field, time: Duration) {
+        while self.next_time().is_some_and(|t| t <= time) {
+            self.step(field);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_read_back() {
+        let mut replay = Replay::new(
+            7,
+            vec![
+                ("--gravity".into(), "nes".into()),
+                ("--next".into(), "3".into()),
+            ],
+        );
+        let actions = [
+            Action::Ticks(1),
+            Action::Ticks(1),
+            Action::Input(InputField::Left),
+            Action::Input(InputField::Hold),
+            Action::Step,
+            Action::HardDrop,
+            Action::Pause,
+        ];
+        for (i, &action) in actions.iter().enumerate() {
+            replay.record(Duration::from_millis(i as u64 * 17), action);
+        }
+        let mut bytes = Vec::new();
+        replay.write_to(&mut bytes).unwrap();
+
+        let read = Replay::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(read.seed, replay.seed);
+        assert_eq!(read.settings, replay.settings);
+        assert_eq!(read.entries, replay.entries);
+    }
+
+    #[test]
+    fn ticks_in_a_row_share_an_entry() {
+        let mut replay = Replay::new(7, Vec::new());
+        for i in 0..3 {
+            replay.record(TICK * i, Action::Ticks(1));
+        }
+        replay.record(TICK * 3, Action::HardDrop);
+        replay.record(TICK * 4, Action::Ticks(1));
+        let times: Vec<_> = replay.entries.iter().map(|e| e.time).collect();
+        assert_eq!(times, [Duration::ZERO, TICK * 3, TICK * 4]);
+        assert_eq!(replay.entries[0].action, Action::Ticks(3));
+
+        // played back they are spread out again
+        let mut player = Player::new(replay);
+        let mut field = Field::with_seed(7);
+        let mut played = Vec::new();
+        while let Some(time) = player.next_time() {
+            played.push(time);
+            player.step(&mut field);
+        }
+        assert_eq!(played, [0, 1, 2, 3, 4].map(|i| TICK * i));
+    }
+
+    #[test]
+    fn cut_off_file() {
+        let mut bytes = Vec::new();
+        Replay::new(7, vec![("--width".into(), "12".into())])
+            .write_to(&mut bytes)
+            .unwrap();
+        bytes.truncate(bytes.len() - 1);
+        assert!(Replay::read_from(&mut bytes.as_slice()).is_err());
+    }
+}
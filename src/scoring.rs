@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+/// Kind of T-spin a locked figure made.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TSpin {
+    None,
+    Mini,
+    Full,
+}
+
+/// Everything a scoring rule may look at when a figure locks.
+#[derive(Copy, Clone, Debug)]
+pub struct LineClear {
+    /// Number of cleared lines, 0 when the lock cleared nothing.
+    pub lines: u32,
+    pub tspin: TSpin,
+    /// Topmost cleared row, counted from the top of the field.
+    pub min_row: usize,
+    pub field_height: usize,
+    pub level: u32,
+    /// Line clears right before this one without a lock in between that
+    /// cleared nothing.
+    pub combo: u32,
+    /// The previous line clear was a difficult one.
+    pub back_to_back: bool,
+    /// The field is empty after the clear.
+    pub perfect_clear: bool,
+}
+
+impl LineClear {
+    /// Tetrises and T-spins that clear lines keep a back-to-back chain
+    /// going.
+    pub fn is_difficult(&self) -> bool {
+        self.lines == 4 || (self.tspin != TSpin::None && self.lines > 0)
+    }
+}
+
+#[typetag::serde]
+pub trait ScoringRule {
+    /// Points for locking a figure, called on every lock.
+    fn lock(&self, clear: &LineClear) -> u32;
+    /// Points for `rows` rows moved down by soft drop.
+    fn soft_drop(&self, rows: u32) -> u32;
+    /// Points for `rows` rows fallen during a hard drop.
+    fn hard_drop(&self, rows: u32) -> u32;
+}
+
+/// The scoring table of the Tetris guideline.
+#[derive(Serialize, Deserialize)]
+pub struct Guideline;
+
+#[typetag::serde]
+impl ScoringRule for Guideline {
+    fn lock(&self, clear: &LineClear) -> u32 {
+        let base = match (clear.tspin, clear.lines) {
+            (TSpin::None, 0) => 0,
+            (TSpin::None, 1) => 100,
+            (TSpin::None, 2) => 300,
+            (TSpin::None, 3) => 500,
+            (TSpin::None, _) => 800,
+            (TSpin::Mini, 0) => 100,
+            (TSpin::Mini, 1) => 200,
+            (TSpin::Mini, _) => 400,
+            (TSpin::Full, 0) => 400,
+            (TSpin::Full, 1) => 800,
+            (TSpin::Full, 2) => 1200,
+            (TSpin::Full, _) => 1600,
+        };
+        let base = if clear.is_difficult() && clear.back_to_back {
+            base * 3 / 2
+        } else {
+            base
+        };
+        let combo = if clear.lines > 0 { 50 * clear.combo } else { 0 };
+        let perfect_clear = if clear.perfect_clear {
+            match clear.lines {
+                1 => 800,
+                2 => 1200,
+                3 => 1800,
+                _ if clear.back_to_back => 3200,
+                _ => 2000,
+            }
+        } else {
+            0
+        };
+        (base + combo + perfect_clear) * clear.level
+    }
+
+    fn soft_drop(&self, rows: u32) -> u32 {
+        rows
+    }
+
+    fn hard_drop(&self, rows: u32) -> u32 {
+        2 * rows
+    }
+}
+
+/// The original scoring of this game: cleared lines are worth more the
+/// higher up they were.
+#[derive(Serialize, Deserialize)]
+pub struct Classic;
+
+#[typetag::serde]
+impl ScoringRule for Classic {
+    fn lock(&self, clear: &LineClear) -> u32 {
+        if clear.lines == 0 {
+            return 0;
+        }
+        clear.lines.saturating_mul(
+            2_u32.saturating_pow(
+                (clear.field_height - clear.min_row - 1) as u32,
+            ),
+        )
+    }
+
+    fn soft_drop(&self, rows: u32) -> u32 {
+        rows
+    }
+
+    fn hard_drop(&self, rows: u32) -> u32 {
+        2 * rows
+    }
+}